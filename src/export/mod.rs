@@ -0,0 +1,204 @@
+use serde_json::{json, Value};
+use std::fs::{self, File};
+use std::io::{Error, Result};
+use std::path::{Path, PathBuf};
+use substrate_archive::archive::{ArchiveService, BatchOptions, Selections};
+use table::{Cell, Table, TABLES};
+use tracing::info;
+use writer::{CsvWriter, ParquetWriter, TableWriter};
+
+mod table;
+mod writer;
+
+const STATE_FILE: &str = "export.json";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Parquet,
+    Csv,
+}
+
+impl ExportFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Parquet => "parquet",
+            ExportFormat::Csv => "csv",
+        }
+    }
+
+    fn writer(&self, file: File, table: &Table) -> Result<Box<dyn TableWriter>> {
+        Ok(match self {
+            ExportFormat::Parquet => Box::new(ParquetWriter::new(file, table)?),
+            ExportFormat::Csv => Box::new(CsvWriter::new(file, table)?),
+        })
+    }
+}
+
+/// Block range and items to write to files of `output` directory.
+pub struct ExportOptions {
+    pub from_block: i32,
+    pub to_block: Option<i32>,
+    pub selections: Selections,
+    pub output: PathBuf,
+    pub format: ExportFormat,
+    /// Maximum number of rows in a parquet row group
+    pub row_group_size: usize,
+    /// Number of blocks covered by a single file of each table
+    pub blocks_per_file: u32,
+}
+
+/// Writes events, calls and extrinsics of a block range to a file per table
+/// for every `blocks_per_file` blocks.
+///
+/// Files are named after the block range they cover, e.g.
+/// `events/0000000000-0000099999.parquet`. A file appears only when it's complete
+/// and the next block to export is saved in `export.json` afterwards,
+/// so an interrupted export continues from the last written file when it's restarted.
+pub async fn export(archive: &(dyn ArchiveService + Sync), options: &ExportOptions) -> Result<()> {
+    let to_block = match options.to_block {
+        Some(to_block) => to_block,
+        None => archive.status().await.map_err(archive_error)?.head as i32,
+    };
+    let mut from_block = resume(options)?;
+    if from_block > to_block {
+        info!(to_block, "nothing to export");
+        return Ok(());
+    }
+    for table in TABLES {
+        fs::create_dir_all(options.output.join(table.name))?;
+    }
+    let blocks_per_file = i32::try_from(options.blocks_per_file.max(1)).unwrap_or(i32::MAX);
+    while from_block <= to_block {
+        let last_block = to_block.min(from_block.saturating_add(blocks_per_file - 1));
+        export_part(archive, options, from_block, last_block).await?;
+        save_state(
+            &options.output,
+            options.from_block,
+            last_block.saturating_add(1),
+        )?;
+        info!(from_block, to_block = last_block, "blocks exported");
+        if last_block == i32::MAX {
+            break;
+        }
+        from_block = last_block + 1;
+    }
+    Ok(())
+}
+
+async fn export_part(
+    archive: &(dyn ArchiveService + Sync),
+    options: &ExportOptions,
+    from_block: i32,
+    to_block: i32,
+) -> Result<()> {
+    let name = format!(
+        "{:010}-{:010}.{}",
+        from_block,
+        to_block,
+        options.format.extension()
+    );
+    let mut parts = Vec::with_capacity(TABLES.len());
+    for table in TABLES {
+        let path = options.output.join(table.name).join(&name);
+        let tmp = path.with_extension("tmp");
+        let writer = options.format.writer(File::create(&tmp)?, table)?;
+        parts.push(Part {
+            table,
+            writer,
+            rows: vec![],
+            tmp,
+            path,
+        });
+    }
+
+    let mut batch_options = BatchOptions {
+        limit: None,
+        from_block,
+        to_block: Some(to_block),
+        from_pos: None,
+        max_bytes: None,
+        time_limit_ms: None,
+        max_blocks: None,
+        include_all_blocks: false,
+        parent_hash: None,
+        selections: options.selections.clone(),
+        progress: None,
+    };
+    loop {
+        let response = archive.batch(&batch_options).await.map_err(archive_error)?;
+        for batch in &response.data {
+            let height = batch.header.height;
+            for part in &mut parts {
+                for item in (part.table.items)(batch) {
+                    part.rows.push(part.table.row(height, item));
+                }
+                let row_group_size = options.row_group_size.max(1);
+                while part.rows.len() >= row_group_size {
+                    let rest = part.rows.split_off(row_group_size);
+                    part.writer.write(&part.rows)?;
+                    part.rows = rest;
+                }
+            }
+        }
+        match response.next_block {
+            Some(next_block) if next_block <= to_block => {
+                batch_options.from_block = next_block;
+                batch_options.from_pos = response.next_pos;
+            }
+            _ => break,
+        }
+    }
+
+    for mut part in parts {
+        part.writer.write(&part.rows)?;
+        part.writer.close()?;
+        fs::rename(&part.tmp, &part.path)?;
+    }
+    Ok(())
+}
+
+struct Part {
+    table: &'static Table,
+    writer: Box<dyn TableWriter>,
+    rows: Vec<Vec<Cell>>,
+    tmp: PathBuf,
+    path: PathBuf,
+}
+
+// first block to export taking a previous run into account
+fn resume(options: &ExportOptions) -> Result<i32> {
+    let path = options.output.join(STATE_FILE);
+    if !path.exists() {
+        return Ok(options.from_block);
+    }
+    let state: Value = serde_json::from_slice(&fs::read(&path)?)?;
+    let from_block = state["fromBlock"].as_i64();
+    let next_block = state["nextBlock"].as_i64();
+    match (from_block, next_block) {
+        (Some(from_block), Some(next_block)) if from_block == i64::from(options.from_block) => {
+            info!(next_block, "resuming export");
+            Ok(next_block as i32)
+        }
+        (Some(from_block), Some(..)) => Err(Error::other(format!(
+            "{} has an export starting from block {}",
+            options.output.display(),
+            from_block
+        ))),
+        _ => Err(Error::other(format!("invalid {}", path.display()))),
+    }
+}
+
+fn save_state(output: &Path, from_block: i32, next_block: i32) -> Result<()> {
+    let state = json!({
+        "fromBlock": from_block,
+        "nextBlock": next_block,
+    });
+    let path = output.join(STATE_FILE);
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, serde_json::to_vec_pretty(&state)?)?;
+    fs::rename(&tmp, &path)
+}
+
+fn archive_error(err: substrate_archive::error::Error) -> Error {
+    Error::other(err.to_string())
+}
@@ -0,0 +1,136 @@
+use serde_json::Value;
+use substrate_archive::entities::Batch;
+
+#[derive(Clone, Copy)]
+pub enum Kind {
+    Int,
+    Bool,
+    Text,
+}
+
+pub struct Column {
+    pub name: &'static str,
+    /// Field of a batch item the column is filled from
+    pub field: &'static str,
+    pub kind: Kind,
+    pub required: bool,
+}
+
+const fn column(name: &'static str, field: &'static str, kind: Kind) -> Column {
+    Column {
+        name,
+        field,
+        kind,
+        required: false,
+    }
+}
+
+const fn required(name: &'static str, field: &'static str, kind: Kind) -> Column {
+    Column {
+        name,
+        field,
+        kind,
+        required: true,
+    }
+}
+
+/// Item type of a batch written to its own files.
+pub struct Table {
+    pub name: &'static str,
+    pub columns: &'static [Column],
+    pub items: fn(&Batch) -> &[Value],
+}
+
+pub enum Cell {
+    Int(i64),
+    Bool(bool),
+    Text(String),
+    Null,
+}
+
+// every table starts with the height of the item block
+const BLOCK_HEIGHT: Column = required("block_height", "", Kind::Int);
+
+pub const EVENTS: Table = Table {
+    name: "events",
+    items: |batch| &batch.events,
+    columns: &[
+        BLOCK_HEIGHT,
+        required("id", "id", Kind::Text),
+        column("index_in_block", "indexInBlock", Kind::Int),
+        column("phase", "phase", Kind::Text),
+        column("extrinsic_id", "extrinsicId", Kind::Text),
+        column("call_id", "callId", Kind::Text),
+        required("name", "name", Kind::Text),
+        column("args", "args", Kind::Text),
+        required("pos", "pos", Kind::Int),
+    ],
+};
+
+pub const CALLS: Table = Table {
+    name: "calls",
+    items: |batch| &batch.calls,
+    columns: &[
+        BLOCK_HEIGHT,
+        required("id", "id", Kind::Text),
+        column("parent_id", "parentId", Kind::Text),
+        column("extrinsic_id", "extrinsicId", Kind::Text),
+        required("name", "name", Kind::Text),
+        column("args", "args", Kind::Text),
+        column("success", "success", Kind::Bool),
+        column("error", "error", Kind::Text),
+        column("origin", "origin", Kind::Text),
+        required("pos", "pos", Kind::Int),
+    ],
+};
+
+pub const EXTRINSICS: Table = Table {
+    name: "extrinsics",
+    items: |batch| &batch.extrinsics,
+    columns: &[
+        BLOCK_HEIGHT,
+        required("id", "id", Kind::Text),
+        column("index_in_block", "indexInBlock", Kind::Int),
+        column("version", "version", Kind::Int),
+        column("signature", "signature", Kind::Text),
+        column("call_id", "callId", Kind::Text),
+        column("fee", "fee", Kind::Text),
+        column("tip", "tip", Kind::Text),
+        column("success", "success", Kind::Bool),
+        column("error", "error", Kind::Text),
+        column("hash", "hash", Kind::Text),
+        required("pos", "pos", Kind::Int),
+    ],
+};
+
+pub const TABLES: [&Table; 3] = [&EVENTS, &CALLS, &EXTRINSICS];
+
+impl Table {
+    /// Flattens an item to cells in the order of the table columns.
+    pub fn row(&self, block_height: i64, item: &Value) -> Vec<Cell> {
+        self.columns
+            .iter()
+            .map(|column| {
+                if column.field.is_empty() {
+                    return Cell::Int(block_height);
+                }
+                let cell = match (item.get(column.field), column.kind) {
+                    (None | Some(Value::Null), _) => Cell::Null,
+                    (Some(value), Kind::Int) => value.as_i64().map_or(Cell::Null, Cell::Int),
+                    (Some(value), Kind::Bool) => value.as_bool().map_or(Cell::Null, Cell::Bool),
+                    (Some(Value::String(value)), Kind::Text) => Cell::Text(value.clone()),
+                    // nested values like args are kept as JSON
+                    (Some(value), Kind::Text) => Cell::Text(value.to_string()),
+                };
+                match cell {
+                    Cell::Null if column.required => match column.kind {
+                        Kind::Int => Cell::Int(0),
+                        Kind::Bool => Cell::Bool(false),
+                        Kind::Text => Cell::Text(String::new()),
+                    },
+                    cell => cell,
+                }
+            })
+            .collect()
+    }
+}
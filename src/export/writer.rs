@@ -0,0 +1,150 @@
+use super::table::{Cell, Kind, Table};
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::data_type::{BoolType, ByteArray, ByteArrayType, Int64Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+use std::fs::File;
+use std::io::{Error, Result};
+use std::sync::Arc;
+
+/// Destination of rows of a single table.
+pub trait TableWriter {
+    /// Writes rows as one row group if the format has them.
+    fn write(&mut self, rows: &[Vec<Cell>]) -> Result<()>;
+    fn close(self: Box<Self>) -> Result<()>;
+}
+
+pub struct ParquetWriter {
+    writer: SerializedFileWriter<File>,
+    kinds: Vec<Kind>,
+}
+
+impl ParquetWriter {
+    pub fn new(file: File, table: &Table) -> Result<ParquetWriter> {
+        let fields: Vec<String> = table
+            .columns
+            .iter()
+            .map(|column| {
+                let repetition = if column.required {
+                    "REQUIRED"
+                } else {
+                    "OPTIONAL"
+                };
+                match column.kind {
+                    Kind::Int => format!("{} INT64 {};", repetition, column.name),
+                    Kind::Bool => format!("{} BOOLEAN {};", repetition, column.name),
+                    Kind::Text => format!("{} BYTE_ARRAY {} (UTF8);", repetition, column.name),
+                }
+            })
+            .collect();
+        let message = format!("message {} {{ {} }}", table.name, fields.join(" "));
+        let schema = parse_message_type(&message).map_err(Error::other)?;
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::ZSTD(ZstdLevel::default()))
+            .build();
+        let writer = SerializedFileWriter::new(file, Arc::new(schema), Arc::new(properties))
+            .map_err(Error::other)?;
+        let kinds = table.columns.iter().map(|column| column.kind).collect();
+        Ok(ParquetWriter { writer, kinds })
+    }
+
+    fn write_row_group(&mut self, rows: &[Vec<Cell>]) -> parquet::errors::Result<()> {
+        let mut row_group = self.writer.next_row_group()?;
+        for (index, kind) in self.kinds.iter().enumerate() {
+            let mut column = match row_group.next_column()? {
+                Some(column) => column,
+                None => break,
+            };
+            // nulls are encoded by definition levels and skipped in values
+            let levels: Vec<i16> = rows
+                .iter()
+                .map(|row| i16::from(!matches!(row[index], Cell::Null)))
+                .collect();
+            let cells = rows.iter().map(|row| &row[index]);
+            match kind {
+                Kind::Int => {
+                    let values: Vec<i64> = cells
+                        .filter_map(|cell| match cell {
+                            Cell::Int(value) => Some(*value),
+                            _ => None,
+                        })
+                        .collect();
+                    column
+                        .typed::<Int64Type>()
+                        .write_batch(&values, Some(&levels), None)?;
+                }
+                Kind::Bool => {
+                    let values: Vec<bool> = cells
+                        .filter_map(|cell| match cell {
+                            Cell::Bool(value) => Some(*value),
+                            _ => None,
+                        })
+                        .collect();
+                    column
+                        .typed::<BoolType>()
+                        .write_batch(&values, Some(&levels), None)?;
+                }
+                Kind::Text => {
+                    let values: Vec<ByteArray> = cells
+                        .filter_map(|cell| match cell {
+                            Cell::Text(value) => Some(ByteArray::from(value.as_bytes().to_vec())),
+                            _ => None,
+                        })
+                        .collect();
+                    column
+                        .typed::<ByteArrayType>()
+                        .write_batch(&values, Some(&levels), None)?;
+                }
+            }
+            column.close()?;
+        }
+        row_group.close()?;
+        Ok(())
+    }
+}
+
+impl TableWriter for ParquetWriter {
+    fn write(&mut self, rows: &[Vec<Cell>]) -> Result<()> {
+        if rows.is_empty() {
+            return Ok(());
+        }
+        self.write_row_group(rows).map_err(Error::other)
+    }
+
+    fn close(self: Box<Self>) -> Result<()> {
+        self.writer.close().map_err(Error::other)?;
+        Ok(())
+    }
+}
+
+pub struct CsvWriter {
+    writer: csv::Writer<File>,
+}
+
+impl CsvWriter {
+    pub fn new(file: File, table: &Table) -> Result<CsvWriter> {
+        let mut writer = csv::Writer::from_writer(file);
+        writer.write_record(table.columns.iter().map(|column| column.name))?;
+        Ok(CsvWriter { writer })
+    }
+}
+
+impl TableWriter for CsvWriter {
+    fn write(&mut self, rows: &[Vec<Cell>]) -> Result<()> {
+        for row in rows {
+            let record = row.iter().map(|cell| match cell {
+                Cell::Int(value) => value.to_string(),
+                Cell::Bool(value) => value.to_string(),
+                Cell::Text(value) => value.clone(),
+                Cell::Null => String::new(),
+            });
+            self.writer.write_record(record)?;
+        }
+        Ok(())
+    }
+
+    fn close(mut self: Box<Self>) -> Result<()> {
+        self.writer.flush()
+    }
+}
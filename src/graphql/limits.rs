@@ -0,0 +1,83 @@
+use async_graphql::extensions::{Extension, ExtensionContext, ExtensionFactory, NextValidation};
+use async_graphql::{ErrorExtensions, ServerError, ValidationResult};
+use std::sync::Arc;
+use substrate_archive::archive::Selections;
+
+/// Bounds of a request, 0 disables a limit.
+#[derive(Clone, Copy, Default)]
+pub struct QueryLimits {
+    pub max_depth: usize,
+    pub max_complexity: usize,
+    /// Total number of selections of a `batch` query
+    pub max_selections: usize,
+    /// Total number of topics in EVM log filters of a `batch` query
+    pub max_topics: usize,
+}
+
+fn exceeded(limit: usize, value: usize) -> bool {
+    limit != 0 && value > limit
+}
+
+fn limit_error(code: &'static str, message: String) -> async_graphql::Error {
+    async_graphql::Error::new(message).extend_with(|_, extensions| extensions.set("code", code))
+}
+
+impl QueryLimits {
+    pub fn check_selections(&self, selections: &Selections) -> async_graphql::Result<()> {
+        let count = selections.count();
+        if exceeded(self.max_selections, count) {
+            let message = format!(
+                "query has {} selections, the limit is {}",
+                count, self.max_selections
+            );
+            return Err(limit_error("TOO_MANY_SELECTIONS", message));
+        }
+        let count = selections.topic_count();
+        if exceeded(self.max_topics, count) {
+            let message = format!(
+                "query has {} topics in log filters, the limit is {}",
+                count, self.max_topics
+            );
+            return Err(limit_error("TOO_MANY_TOPICS", message));
+        }
+        Ok(())
+    }
+}
+
+impl ExtensionFactory for QueryLimits {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(*self)
+    }
+}
+
+#[async_trait::async_trait]
+impl Extension for QueryLimits {
+    // checks the same depth and complexity as `limit_depth` and `limit_complexity`
+    // of the schema builder but reports them with error codes
+    async fn validation(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        next: NextValidation<'_>,
+    ) -> Result<ValidationResult, Vec<ServerError>> {
+        let result = next.run(ctx).await?;
+        let error = if exceeded(self.max_depth, result.depth) {
+            let message = format!(
+                "query depth {} exceeds the limit of {}",
+                result.depth, self.max_depth
+            );
+            Some(limit_error("QUERY_TOO_DEEP", message))
+        } else if exceeded(self.max_complexity, result.complexity) {
+            let message = format!(
+                "query complexity {} exceeds the limit of {}",
+                result.complexity, self.max_complexity
+            );
+            Some(limit_error("QUERY_TOO_COMPLEX", message))
+        } else {
+            None
+        };
+        match error {
+            Some(error) => Err(vec![error.into_server_error(Default::default())]),
+            None => Ok(result),
+        }
+    }
+}
@@ -1,18 +1,26 @@
-use async_graphql::{Context, Object, Result};
+use async_graphql::{
+    Context, EmptyMutation, EmptySubscription, ErrorExtensions, Object, Request, Response, Result,
+    Schema,
+};
 use inputs::{
     AcalaEvmEventSelectionInput, CallSelectionInput, ContractsEventSelectionInput,
     EthTransactSelectionInput, EventSelectionInput, EvmLogSelectionInput,
     GearMessageEnqueuedSelectionInput, GearUserMessageSentSelectionInput,
 };
+use limits::QueryLimits;
 use std::sync::{Arc, Mutex};
-use substrate_archive::archive::{ArchiveService, BatchOptions, Selections};
+use substrate_archive::archive::{ArchiveService, BatchOptions, ScanProgress, Selections};
 use substrate_archive::entities::{Batch, Metadata, Status};
+use substrate_archive::error::Error;
 use substrate_archive::selection::{
     AcalaEvmEventSelection, CallSelection, ContractsEventSelection, EthTransactSelection,
     EventSelection, EvmLogSelection, GearMessageEnqueuedSelection, GearUserMessageSentSelection,
 };
 
 mod inputs;
+pub mod limits;
+pub mod persisted;
+pub mod sdl;
 
 pub struct EvmSupport(pub bool);
 
@@ -38,7 +46,47 @@ fn is_gear_supported(ctx: &Context<'_>) -> bool {
     ctx.data_unchecked::<GearSupport>().0
 }
 
-pub struct NextBlock(pub Option<i32>);
+struct NextBlock {
+    block: Option<i32>,
+    pos: Option<i64>,
+}
+
+/// Executes a request putting `nextBlock` and `nextPos` of a `batch` query
+/// next to its data.
+pub async fn execute(
+    schema: &Schema<QueryRoot, EmptyMutation, EmptySubscription>,
+    request: Request,
+) -> Response {
+    let next_block = Arc::new(Mutex::new(NextBlock {
+        block: None,
+        pos: None,
+    }));
+    let mut response = schema.execute(request.data(next_block.clone())).await;
+    let lock = next_block.lock().unwrap();
+    if let Some(next_block) = lock.block {
+        let mut value = response.data.into_json().unwrap();
+        let data = value.as_object_mut().unwrap();
+        let batch = data.get("batch").unwrap();
+        let mut wrapped = serde_json::json!({
+            "data": batch,
+            "nextBlock": next_block
+        });
+        if let Some(next_pos) = lock.pos {
+            wrapped["nextPos"] = next_pos.into();
+        }
+        data.insert("batch".to_string(), wrapped);
+        response.data = serde_json::to_value(data).unwrap().try_into().unwrap();
+    }
+    response
+}
+
+/// Parameters and progress of a running batch query
+pub struct BatchActivity {
+    pub from_block: i32,
+    pub to_block: Option<i32>,
+    pub selections: Selections,
+    pub progress: ScanProgress,
+}
 
 pub struct QueryRoot {
     pub archive: Box<dyn ArchiveService + Send + Sync>,
@@ -53,6 +101,11 @@ impl QueryRoot {
         limit: Option<i32>,
         #[graphql(default = 0)] from_block: i32,
         to_block: Option<i32>,
+        from_pos: Option<i64>,
+        parent_hash: Option<String>,
+        max_bytes: Option<usize>,
+        time_limit_ms: Option<u32>,
+        max_blocks: Option<u32>,
         #[graphql(name = "evmLogs", visible = "is_evm_supported")] evm_log_selections: Option<
             Vec<EvmLogSelectionInput>,
         >,
@@ -84,17 +137,35 @@ impl QueryRoot {
             acala_evm_executed: self.unwrap_selections::<AcalaEvmEventSelectionInput, AcalaEvmEventSelection>(acala_evm_executed_selections),
             acala_evm_executed_failed: self.unwrap_selections::<AcalaEvmEventSelectionInput, AcalaEvmEventSelection>(acala_evm_executed_failed_selections),
         };
+        ctx.data_unchecked::<QueryLimits>()
+            .check_selections(&selections)?;
+        let progress = ScanProgress::default();
+        if let Some(activity) = ctx.data_opt::<Arc<Mutex<Option<BatchActivity>>>>() {
+            *activity.lock().unwrap() = Some(BatchActivity {
+                from_block,
+                to_block,
+                selections: selections.clone(),
+                progress: progress.clone(),
+            });
+        }
         let options = BatchOptions {
             limit,
             from_block,
             to_block,
+            from_pos,
+            max_bytes,
+            time_limit_ms,
+            max_blocks,
             include_all_blocks: include_all_blocks.unwrap_or(false),
+            parent_hash,
             selections,
+            progress: Some(progress),
         };
-        let resp = self.archive.batch(&options).await?;
+        let resp = self.archive.batch(&options).await.map_err(batch_error)?;
         if let Some(next) = resp.next_block {
             let mut next_block = next_block.lock().unwrap();
-            next_block.0 = Some(next);
+            next_block.block = Some(next);
+            next_block.pos = resp.next_pos;
         }
         Ok(resp.data)
     }
@@ -115,6 +186,19 @@ impl QueryRoot {
     }
 }
 
+// a reorg gets the parent hash known to the archive so a client can find the fork point
+fn batch_error(err: Error) -> async_graphql::Error {
+    let error = async_graphql::Error::new(err.to_string());
+    match err {
+        Error::Reorg { height, actual, .. } => error.extend_with(|_, extensions| {
+            extensions.set("code", "REORG");
+            extensions.set("height", height);
+            extensions.set("parentHash", actual.as_str());
+        }),
+        Error::Internal(..) => error,
+    }
+}
+
 impl QueryRoot {
     fn unwrap_selections<T, U: From<T>>(&self, selections: Option<Vec<T>>) -> Vec<U> {
         selections.map_or_else(Vec::new, |selections| {
@@ -0,0 +1,140 @@
+use async_graphql::extensions::apollo_persisted_queries::{CacheStorage, LruCacheStorage};
+use async_graphql::extensions::{
+    Extension, ExtensionContext, ExtensionFactory, NextPrepareRequest,
+};
+use async_graphql::parser::parse_query;
+use async_graphql::parser::types::ExecutableDocument;
+use async_graphql::{ErrorExtensionValues, Request, ServerError, ServerResult};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+use std::sync::Arc;
+
+fn sha256(query: &str) -> String {
+    format!("{:x}", Sha256::digest(query.as_bytes()))
+}
+
+/// Queries registered ahead of time, keyed by sha256 hash of their text.
+#[derive(Clone, Default)]
+pub struct PersistedQueries(Arc<HashMap<String, ExecutableDocument>>);
+
+impl PersistedQueries {
+    /// Parses a JSON object mapping sha256 hashes to query documents.
+    pub fn from_json(json: &str) -> Result<PersistedQueries, Error> {
+        let queries: HashMap<String, String> = serde_json::from_str(json)?;
+        let mut documents = HashMap::with_capacity(queries.len());
+        for (hash, query) in queries {
+            let hash = hash.to_ascii_lowercase();
+            if sha256(&query) != hash {
+                let message = format!("{} isn't sha256 hash of its query", hash);
+                return Err(Error::new(ErrorKind::InvalidData, message));
+            }
+            let document = parse_query(&query)
+                .map_err(|err| Error::new(ErrorKind::InvalidData, format!("{}: {}", hash, err)))?;
+            documents.insert(hash, document);
+        }
+        Ok(PersistedQueries(Arc::new(documents)))
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<PersistedQueries, Error> {
+        let json = std::fs::read_to_string(path)?;
+        PersistedQueries::from_json(&json)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn contains(&self, hash: &str) -> bool {
+        self.0.contains_key(hash)
+    }
+}
+
+/// Storage of the automatic persisted queries extension.
+///
+/// Registered queries are always served, queries sent by clients are remembered
+/// in the LRU cache unless only registered queries are allowed.
+#[derive(Clone)]
+pub struct QueryStorage {
+    registered: PersistedQueries,
+    cache: Option<LruCacheStorage>,
+}
+
+impl QueryStorage {
+    pub fn new(registered: PersistedQueries, cache_size: usize) -> QueryStorage {
+        let cache = (cache_size > 0).then(|| LruCacheStorage::new(cache_size));
+        QueryStorage { registered, cache }
+    }
+}
+
+#[async_trait::async_trait]
+impl CacheStorage for QueryStorage {
+    async fn get(&self, key: String) -> Option<ExecutableDocument> {
+        if let Some(document) = self.registered.0.get(&key) {
+            return Some(document.clone());
+        }
+        match &self.cache {
+            Some(cache) => cache.get(key).await,
+            None => None,
+        }
+    }
+
+    async fn set(&self, key: String, query: ExecutableDocument) {
+        if let Some(cache) = &self.cache {
+            cache.set(key, query).await;
+        }
+    }
+}
+
+/// Rejects requests which query isn't registered, whether it's sent
+/// in full or as a hash.
+pub struct AllowList(pub PersistedQueries);
+
+impl ExtensionFactory for AllowList {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(AllowListExtension(self.0.clone()))
+    }
+}
+
+struct AllowListExtension(PersistedQueries);
+
+#[async_trait::async_trait]
+impl Extension for AllowListExtension {
+    async fn prepare_request(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        request: Request,
+        next: NextPrepareRequest<'_>,
+    ) -> ServerResult<Request> {
+        let hash = if request.query.is_empty() {
+            request
+                .extensions
+                .get("persistedQuery")
+                .and_then(|value| match value {
+                    async_graphql::Value::Object(object) => object.get("sha256Hash"),
+                    _ => None,
+                })
+                .and_then(|value| match value {
+                    async_graphql::Value::String(hash) => Some(hash.to_ascii_lowercase()),
+                    _ => None,
+                })
+        } else {
+            // a hash sent along with the query is verified by the persisted queries extension
+            Some(sha256(&request.query))
+        };
+        if !hash.is_some_and(|hash| self.0.contains(&hash)) {
+            let mut extensions = ErrorExtensionValues::default();
+            extensions.set("code", "PERSISTED_QUERY_NOT_IN_LIST");
+            return Err(ServerError {
+                extensions: Some(extensions),
+                ..ServerError::new("query isn't in the list of persisted queries", None)
+            });
+        }
+        next.run(ctx, request).await
+    }
+}
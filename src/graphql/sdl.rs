@@ -0,0 +1,139 @@
+// `Schema::sdl` exports every registered type and doesn't evaluate `visible`
+// conditions, so SDL is printed from the introspection result instead which
+// hides arguments and types disabled by support flags.
+
+use super::QueryRoot;
+use async_graphql::{EmptyMutation, EmptySubscription, Schema};
+use serde_json::Value;
+use std::fmt::Write;
+
+const INTROSPECTION_QUERY: &str = "
+    query {
+        __schema {
+            queryType { name }
+            types {
+                kind
+                name
+                description
+                fields { name description args { ...InputValue } type { ...TypeRef } }
+                inputFields { ...InputValue }
+                enumValues { name description }
+                possibleTypes { name }
+            }
+        }
+    }
+
+    fragment InputValue on __InputValue {
+        name
+        description
+        defaultValue
+        type { ...TypeRef }
+    }
+
+    fragment TypeRef on __Type {
+        kind
+        name
+        ofType { kind name ofType { kind name ofType { kind name ofType { kind name } } } }
+    }
+";
+
+const BUILTIN_SCALARS: &[&str] = &["String", "Int", "Float", "Boolean", "ID"];
+
+pub async fn export(schema: &Schema<QueryRoot, EmptyMutation, EmptySubscription>) -> String {
+    let response = schema.execute(INTROSPECTION_QUERY).await;
+    assert!(response.errors.is_empty(), "introspection query failed");
+    let data = response.data.into_json().unwrap();
+    let introspection = &data["__schema"];
+
+    let mut sdl = String::new();
+    for ty in introspection["types"].as_array().unwrap() {
+        let name = ty["name"].as_str().unwrap();
+        if name.starts_with("__") || BUILTIN_SCALARS.contains(&name) {
+            continue;
+        }
+        write_description(&mut sdl, &ty["description"], "");
+        match ty["kind"].as_str().unwrap() {
+            "SCALAR" => writeln!(sdl, "scalar {}", name).unwrap(),
+            "OBJECT" => {
+                writeln!(sdl, "type {} {{", name).unwrap();
+                for field in ty["fields"].as_array().unwrap() {
+                    write_description(&mut sdl, &field["description"], "\t");
+                    write!(sdl, "\t{}", field["name"].as_str().unwrap()).unwrap();
+                    let args = field["args"].as_array().unwrap();
+                    if !args.is_empty() {
+                        let args: Vec<String> = args.iter().map(input_value).collect();
+                        write!(sdl, "({})", args.join(", ")).unwrap();
+                    }
+                    writeln!(sdl, ": {}", type_ref(&field["type"])).unwrap();
+                }
+                writeln!(sdl, "}}").unwrap();
+            }
+            "INPUT_OBJECT" => {
+                writeln!(sdl, "input {} {{", name).unwrap();
+                for field in ty["inputFields"].as_array().unwrap() {
+                    write_description(&mut sdl, &field["description"], "\t");
+                    writeln!(sdl, "\t{}", input_value(field)).unwrap();
+                }
+                writeln!(sdl, "}}").unwrap();
+            }
+            "ENUM" => {
+                writeln!(sdl, "enum {} {{", name).unwrap();
+                for value in ty["enumValues"].as_array().unwrap() {
+                    write_description(&mut sdl, &value["description"], "\t");
+                    writeln!(sdl, "\t{}", value["name"].as_str().unwrap()).unwrap();
+                }
+                writeln!(sdl, "}}").unwrap();
+            }
+            "UNION" => {
+                let types: Vec<&str> = ty["possibleTypes"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|ty| ty["name"].as_str().unwrap())
+                    .collect();
+                writeln!(sdl, "union {} = {}", name, types.join(" | ")).unwrap();
+            }
+            kind => panic!("unexpected type kind: {}", kind),
+        }
+        writeln!(sdl).unwrap();
+    }
+    writeln!(sdl, "schema {{").unwrap();
+    writeln!(
+        sdl,
+        "\tquery: {}",
+        introspection["queryType"]["name"].as_str().unwrap()
+    )
+    .unwrap();
+    writeln!(sdl, "}}").unwrap();
+    sdl
+}
+
+fn write_description(sdl: &mut String, description: &Value, indent: &str) {
+    if let Some(description) = description.as_str() {
+        writeln!(sdl, "{}\"\"\"", indent).unwrap();
+        for line in description.lines() {
+            writeln!(sdl, "{}{}", indent, line).unwrap();
+        }
+        writeln!(sdl, "{}\"\"\"", indent).unwrap();
+    }
+}
+
+fn input_value(value: &Value) -> String {
+    let mut sdl = format!(
+        "{}: {}",
+        value["name"].as_str().unwrap(),
+        type_ref(&value["type"])
+    );
+    if let Some(default) = value["defaultValue"].as_str() {
+        write!(sdl, " = {}", default).unwrap();
+    }
+    sdl
+}
+
+fn type_ref(ty: &Value) -> String {
+    match ty["kind"].as_str().unwrap() {
+        "NON_NULL" => format!("{}!", type_ref(&ty["ofType"])),
+        "LIST" => format!("[{}]", type_ref(&ty["ofType"])),
+        _ => ty["name"].as_str().unwrap().to_string(),
+    }
+}
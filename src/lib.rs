@@ -1,38 +1,121 @@
-use async_graphql::{EmptyMutation, EmptySubscription, Schema};
+use async_graphql::extensions::apollo_persisted_queries::ApolloPersistedQueries;
+use async_graphql::{EmptyMutation, EmptySubscription, Request, Response, Schema};
+use graphql::limits::QueryLimits;
+pub use graphql::persisted::PersistedQueries;
+use graphql::persisted::{AllowList, QueryStorage};
 use graphql::{AcalaSupport, ContractsSupport, EvmSupport, GearSupport, QueryRoot};
+pub use server::Ui;
 use sqlx::{Pool, Postgres};
 use std::boxed::Box;
+use std::io::Error;
+use std::path::PathBuf;
+use std::time::Duration;
+use substrate_archive::archive::ArchiveService;
+use substrate_archive::budget::ResponseSizeLimit;
+pub use substrate_archive::memory::MemoryArchive;
+pub use substrate_archive::postgres::hints::IndexHints;
+use substrate_archive::postgres::validation::{Feature, SchemaInspector};
 pub use substrate_archive::postgres::DatabaseType;
 use substrate_archive::postgres::PostgresArchive;
+use tracing::{error, info, warn};
 
+pub mod export;
 mod graphql;
 mod metrics;
 mod server;
 
+#[derive(Clone)]
+enum Backend {
+    Postgres(Pool<Postgres>, Option<DatabaseType>),
+    Memory(MemoryArchive),
+}
+
+/// Executes GraphQL requests against a schema built by `SubstrateGateway::executor`,
+/// requests share its persisted queries cache.
+pub struct Executor {
+    schema: Schema<QueryRoot, EmptyMutation, EmptySubscription>,
+}
+
+impl Executor {
+    pub async fn execute(&self, request: Request) -> Response {
+        graphql::execute(&self.schema, request).await
+    }
+}
+
+#[derive(Clone)]
 pub struct SubstrateGateway {
-    pool: Pool<Postgres>,
-    database_type: DatabaseType,
+    backend: Backend,
     scan_start_value: u16,
     scan_max_value: u32,
     scan_time_limit: u16,
+    max_scan_time_limit: u16,
+    response_size_limit: ResponseSizeLimit,
+    index_hints: IndexHints,
     evm_support: bool,
     acala_support: bool,
     contracts_support: bool,
     gear_support: bool,
+    disable_unsupported_features: bool,
+    admin_token: Option<String>,
+    shutdown_grace_period: u64,
+    compression_level: u32,
+    compression_min_size: usize,
+    persisted_queries: PersistedQueries,
+    persisted_queries_only: bool,
+    apq_cache_size: usize,
+    limits: QueryLimits,
+    ui: Ui,
+    disable_introspection: bool,
+    cors: server::CorsConfig,
+    tls: Option<(PathBuf, PathBuf)>,
+    tls_client_ca: Option<PathBuf>,
+    tls_client_cert_required: bool,
+    confirmation_depth: Option<u32>,
 }
 
 impl SubstrateGateway {
     pub fn new(pool: Pool<Postgres>, database_type: DatabaseType) -> Self {
+        SubstrateGateway::with_backend(Backend::Postgres(pool, Some(database_type)))
+    }
+
+    /// Gateway over a database whose type is detected on startup
+    pub fn with_detected_database_type(pool: Pool<Postgres>) -> Self {
+        SubstrateGateway::with_backend(Backend::Postgres(pool, None))
+    }
+
+    pub fn in_memory(archive: MemoryArchive) -> Self {
+        SubstrateGateway::with_backend(Backend::Memory(archive))
+    }
+
+    fn with_backend(backend: Backend) -> Self {
         SubstrateGateway {
-            pool,
-            database_type,
+            backend,
             scan_start_value: 50,
             scan_max_value: 100_000,
             scan_time_limit: 5000,
+            max_scan_time_limit: 30000,
+            response_size_limit: ResponseSizeLimit::default(),
+            index_hints: IndexHints::default(),
             evm_support: false,
             acala_support: false,
             contracts_support: false,
             gear_support: false,
+            disable_unsupported_features: false,
+            admin_token: None,
+            shutdown_grace_period: 30,
+            compression_level: 4,
+            compression_min_size: 1024,
+            persisted_queries: PersistedQueries::default(),
+            persisted_queries_only: false,
+            apq_cache_size: 1000,
+            limits: QueryLimits::default(),
+            ui: Ui::Playground,
+            disable_introspection: false,
+            cors: server::CorsConfig::default(),
+            tls: None,
+            tls_client_ca: None,
+            tls_client_cert_required: false,
+            confirmation_depth: None,
         }
     }
 
@@ -71,21 +154,396 @@ impl SubstrateGateway {
         self
     }
 
+    /// Upper bound of a time limit requested by a client with `timeLimitMs`
+    pub fn max_scan_time_limit(mut self, value: u16) -> Self {
+        self.max_scan_time_limit = value;
+        self
+    }
+
+    /// Byte budget of a response unless a request asks for its own one
+    pub fn response_size_limit(mut self, value: usize) -> Self {
+        self.response_size_limit.default = value;
+        self
+    }
+
+    /// Upper bound of a byte budget requested by a client
+    pub fn max_response_size_limit(mut self, value: usize) -> Self {
+        self.response_size_limit.max = value;
+        self
+    }
+
+    /// Turn off a feature which database schema lacks required tables or
+    /// indexes instead of refusing to start
+    pub fn disable_unsupported_features(mut self, value: bool) -> Self {
+        self.disable_unsupported_features = value;
+        self
+    }
+
+    /// Enables `/admin` endpoints protected by the bearer token
+    pub fn admin_token(mut self, value: String) -> Self {
+        self.admin_token = Some(value);
+        self
+    }
+
+    /// Time (seconds) in-flight requests have to complete after SIGTERM
+    pub fn shutdown_grace_period(mut self, value: u64) -> Self {
+        self.shutdown_grace_period = value;
+        self
+    }
+
+    /// Level from 1 (fastest) to 9 (best) of gzip, brotli and zstd
+    /// response compression, 0 disables compression
+    pub fn compression_level(mut self, value: u32) -> Self {
+        self.compression_level = value;
+        self
+    }
+
+    /// Responses smaller than this number of bytes are sent uncompressed
+    pub fn compression_min_size(mut self, value: usize) -> Self {
+        self.compression_min_size = value;
+        self
+    }
+
+    /// Queries a client can run by sending the sha256 hash of their text
+    pub fn persisted_queries(mut self, value: PersistedQueries) -> Self {
+        self.persisted_queries = value;
+        self
+    }
+
+    /// Rejects queries which aren't among persisted queries
+    pub fn persisted_queries_only(mut self, value: bool) -> Self {
+        self.persisted_queries_only = value;
+        self
+    }
+
+    /// Number of queries sent by clients remembered for automatic persisted queries,
+    /// 0 disables the cache
+    pub fn apq_cache_size(mut self, value: usize) -> Self {
+        self.apq_cache_size = value;
+        self
+    }
+
+    /// Maximum nesting of fields in a query, 0 disables the limit
+    pub fn max_query_depth(mut self, value: usize) -> Self {
+        self.limits.max_depth = value;
+        self
+    }
+
+    /// Maximum number of fields in a query, 0 disables the limit
+    pub fn max_query_complexity(mut self, value: usize) -> Self {
+        self.limits.max_complexity = value;
+        self
+    }
+
+    /// Maximum number of selections across all lists of a `batch` query,
+    /// 0 disables the limit
+    pub fn max_selections(mut self, value: usize) -> Self {
+        self.limits.max_selections = value;
+        self
+    }
+
+    /// Maximum number of topics across EVM log filters of a `batch` query,
+    /// 0 disables the limit
+    pub fn max_topics(mut self, value: usize) -> Self {
+        self.limits.max_topics = value;
+        self
+    }
+
+    /// Page served on `/`
+    pub fn ui(mut self, value: Ui) -> Self {
+        self.ui = value;
+        self
+    }
+
+    /// Rejects introspection queries
+    pub fn disable_introspection(mut self, value: bool) -> Self {
+        self.disable_introspection = value;
+        self
+    }
+
+    /// Serves a static info page instead of the playground,
+    /// introspection is disabled separately with `disable_introspection`
+    pub fn production(self) -> Self {
+        self.ui(Ui::Info)
+    }
+
+    /// Origins allowed to query `/graphql` from a browser, `*` allows any origin
+    pub fn cors_allowed_origins(mut self, value: Vec<String>) -> Self {
+        self.cors.allowed_origins = value;
+        self
+    }
+
+    /// Methods allowed in cross-origin requests, `POST` by default
+    pub fn cors_allowed_methods(mut self, value: Vec<String>) -> Self {
+        self.cors.allowed_methods = value;
+        self
+    }
+
+    /// Headers allowed in cross-origin requests, `Content-Type`,
+    /// `X-SQUID-PROCESSOR` and `X-REQUEST-ID` by default
+    pub fn cors_allowed_headers(mut self, value: Vec<String>) -> Self {
+        self.cors.allowed_headers = value;
+        self
+    }
+
+    /// Time (seconds) a browser may cache a preflight response
+    pub fn cors_max_age(mut self, value: usize) -> Self {
+        self.cors.max_age = value;
+        self
+    }
+
+    /// Serves https with the PEM encoded certificate chain and private key,
+    /// both are read again on SIGHUP
+    pub fn tls(mut self, cert: PathBuf, key: PathBuf) -> Self {
+        self.tls = Some((cert, key));
+        self
+    }
+
+    /// Verifies certificates presented by clients of https against CAs from the PEM file
+    pub fn tls_client_ca(mut self, value: PathBuf) -> Self {
+        self.tls_client_ca = Some(value);
+        self
+    }
+
+    /// Rejects clients of https which don't present a certificate, see `tls_client_ca`
+    pub fn tls_client_cert_required(mut self, value: bool) -> Self {
+        self.tls_client_cert_required = value;
+        self
+    }
+
+    /// Reports `finalizedHead` this number of blocks behind the head instead of
+    /// considering every ingested block finalized
+    pub fn confirmation_depth(mut self, value: u32) -> Self {
+        self.confirmation_depth = Some(value);
+        self
+    }
+
+    pub fn index_hints(mut self, value: IndexHints) -> Self {
+        self.index_hints = value;
+        self
+    }
+
     pub async fn run(&self) -> std::io::Result<()> {
-        let archive = Box::new(PostgresArchive::new(
-            self.pool.clone(),
-            self.database_type.clone(),
-            self.scan_start_value,
-            self.scan_max_value,
-            self.scan_time_limit,
-        ));
+        let (gateway, archive) = self.prepare().await?;
+        let grace_period = Duration::from_secs(self.shutdown_grace_period);
+        let compress = server::Compress {
+            level: self.compression_level,
+            min_size: self.compression_min_size,
+        };
+        if self.disable_introspection && self.ui != Ui::Info {
+            // schema docs and autocompletion of the page rely on introspection
+            warn!(
+                ui = format!("{:?}", self.ui).as_str(),
+                "introspection is disabled, the page served on / won't load the schema"
+            );
+        }
+        let index = server::index::page(self.ui, &gateway.supports());
+        let tls = self.tls.clone().map(|(cert, key)| server::TlsConfig {
+            cert,
+            key,
+            client_ca: self.tls_client_ca.clone(),
+            client_cert_required: self.tls_client_cert_required,
+        });
+        server::run(
+            gateway.schema(archive),
+            self.admin_token.clone(),
+            grace_period,
+            compress,
+            index,
+            self.cors.clone(),
+            tls,
+        )
+        .await?;
+        if let Backend::Postgres(pool, _) = &self.backend {
+            pool.close().await;
+            info!("database pool closed");
+        }
+        Ok(())
+    }
+
+    /// Checks the backend and builds the schema once for executing
+    /// GraphQL requests without the http server.
+    pub async fn executor(&self) -> std::io::Result<Executor> {
+        let (gateway, archive) = self.prepare().await?;
+        Ok(Executor {
+            schema: gateway.schema(archive),
+        })
+    }
+
+    // gateway with features the backend supports along with its archive
+    async fn prepare(
+        &self,
+    ) -> std::io::Result<(SubstrateGateway, Box<dyn ArchiveService + Send + Sync>)> {
+        let (gateway, archive): (_, Box<dyn ArchiveService + Send + Sync>) = match &self.backend {
+            Backend::Postgres(pool, database_type) => {
+                let database_type = self.resolve_database_type(pool, database_type).await?;
+                let gateway = self.check_schema(pool, &database_type).await?;
+                let archive = PostgresArchive::new(
+                    pool.clone(),
+                    database_type,
+                    self.scan_start_value,
+                    self.scan_max_value,
+                    self.scan_time_limit,
+                )
+                .index_hints(self.index_hints.clone())
+                .max_scan_time_limit(self.max_scan_time_limit)
+                .response_size_limit(self.response_size_limit)
+                .confirmation_depth(self.confirmation_depth);
+                (gateway, Box::new(archive))
+            }
+            Backend::Memory(archive) => {
+                let archive = archive
+                    .clone()
+                    .response_size_limit(self.response_size_limit)
+                    .confirmation_depth(self.confirmation_depth);
+                (self.clone(), Box::new(archive))
+            }
+        };
+        Ok((gateway, archive))
+    }
+
+    /// GraphQL SDL of the schema served with the current support flags.
+    pub async fn sdl(&self) -> String {
+        let archive = Box::new(MemoryArchive::default());
+        graphql::sdl::export(&self.schema(archive)).await
+    }
+
+    async fn resolve_database_type(
+        &self,
+        pool: &Pool<Postgres>,
+        database_type: &Option<DatabaseType>,
+    ) -> std::io::Result<DatabaseType> {
+        let (detected, version) = DatabaseType::detect(pool)
+            .await
+            .map_err(|err| Error::other(err.to_string()))?;
+        info!(
+            engine = ?detected,
+            version = version.as_str(),
+            "database engine detected"
+        );
+        match database_type {
+            Some(database_type) => {
+                if *database_type != detected {
+                    warn!(
+                        engine = ?database_type,
+                        detected = ?detected,
+                        "database type is overridden"
+                    );
+                }
+                Ok(database_type.clone())
+            }
+            None => Ok(detected),
+        }
+    }
+
+    // verifies that the database has everything enabled features rely on
+    // and returns the gateway with features which can be served
+    async fn check_schema(
+        &self,
+        pool: &Pool<Postgres>,
+        database_type: &DatabaseType,
+    ) -> std::io::Result<SubstrateGateway> {
+        let inspector = SchemaInspector::load(pool, database_type.clone())
+            .await
+            .map_err(|err| Error::other(err.to_string()))?
+            .index_hints(self.index_hints.clone());
+
+        let missing = inspector.missing(Feature::Substrate);
+        if !missing.is_empty() {
+            let report = missing.join(", ");
+            error!(
+                feature = "substrate",
+                missing = report.as_str(),
+                "database schema mismatch"
+            );
+            let message = format!("database schema lacks {}", report);
+            return Err(Error::other(message));
+        }
+
+        let mut gateway = self.clone();
+        let features = [
+            (Feature::Evm, &mut gateway.evm_support, "--evm-support"),
+            (
+                Feature::Acala,
+                &mut gateway.acala_support,
+                "--acala-support",
+            ),
+            (
+                Feature::Contracts,
+                &mut gateway.contracts_support,
+                "--contracts-support",
+            ),
+            (Feature::Gear, &mut gateway.gear_support, "--gear-support"),
+        ];
+        let mut unsupported = vec![];
+        for (feature, enabled, flag) in features {
+            if !*enabled {
+                continue;
+            }
+            let missing = inspector.missing(feature);
+            if missing.is_empty() {
+                continue;
+            }
+            let report = missing.join(", ");
+            if self.disable_unsupported_features {
+                warn!(
+                    feature = feature.to_string().as_str(),
+                    missing = report.as_str(),
+                    "feature disabled due to database schema mismatch"
+                );
+                *enabled = false;
+            } else {
+                error!(
+                    feature = feature.to_string().as_str(),
+                    missing = report.as_str(),
+                    "database schema mismatch"
+                );
+                unsupported.push(format!("{} requires {}", flag, report));
+            }
+        }
+        if !unsupported.is_empty() {
+            let message = format!("database schema mismatch: {}", unsupported.join("; "));
+            return Err(Error::other(message));
+        }
+        Ok(gateway)
+    }
+
+    // names of enabled pallet supports
+    fn supports(&self) -> Vec<&'static str> {
+        [
+            ("evm", self.evm_support),
+            ("acala", self.acala_support),
+            ("contracts", self.contracts_support),
+            ("gear", self.gear_support),
+        ]
+        .into_iter()
+        .filter_map(|(name, enabled)| enabled.then_some(name))
+        .collect()
+    }
+
+    fn schema(
+        &self,
+        archive: Box<dyn ArchiveService + Send + Sync>,
+    ) -> Schema<QueryRoot, EmptyMutation, EmptySubscription> {
         let query = QueryRoot { archive };
-        let schema = Schema::build(query, EmptyMutation, EmptySubscription)
+        let mut builder = Schema::build(query, EmptyMutation, EmptySubscription)
             .data(EvmSupport(self.evm_support))
             .data(AcalaSupport(self.acala_support))
             .data(ContractsSupport(self.contracts_support))
             .data(GearSupport(self.gear_support))
-            .finish();
-        server::run(schema).await
+            .data(self.limits)
+            .extension(self.limits);
+        if self.disable_introspection {
+            builder = builder.disable_introspection();
+        }
+        let mut cache_size = self.apq_cache_size;
+        if self.persisted_queries_only {
+            builder = builder.extension(AllowList(self.persisted_queries.clone()));
+            cache_size = 0;
+        }
+        let storage = QueryStorage::new(self.persisted_queries.clone(), cache_size);
+        builder
+            .extension(ApolloPersistedQueries::new(storage))
+            .finish()
     }
 }
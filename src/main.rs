@@ -1,17 +1,29 @@
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
 use sqlx::postgres::PgPoolOptions;
-use sqlx::Executor;
+use sqlx::{Executor, Pool, Postgres};
+use std::path::PathBuf;
 use std::time::Duration;
-use substrate_gateway::{DatabaseType, SubstrateGateway};
+use substrate_archive::archive::Selections;
+use substrate_archive::postgres::PostgresArchive;
+use substrate_archive::selection::{
+    CallDataSelection, CallSelection, EventDataSelection, EventSelection,
+};
+use substrate_gateway::export::{export, ExportFormat, ExportOptions};
+use substrate_gateway::{
+    DatabaseType, IndexHints, MemoryArchive, PersistedQueries, SubstrateGateway, Ui,
+};
 
 mod logger;
 
 #[derive(Parser, Debug)]
-#[clap(about)]
+#[clap(about, subcommand_negates_reqs = true)]
 struct Args {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
     /// Database connection string
-    #[clap(long)]
-    database_url: String,
+    #[clap(long, required = true)]
+    database_url: Option<String>,
 
     /// Maximum number of connections supported by pool
     #[clap(long, default_value_t = 1)]
@@ -21,9 +33,13 @@ struct Args {
     #[clap(long, default_value_t = 0)]
     database_statement_timeout: u32,
 
-    /// Database type
-    #[clap(long, value_enum, default_value_t = DatabaseType::Postgres)]
-    database_type: DatabaseType,
+    /// Database type, detected automatically if not set
+    #[clap(long, value_enum)]
+    database_type: Option<DatabaseType>,
+
+    /// JSON file overriding index hints used in database queries
+    #[clap(long)]
+    index_hints: Option<String>,
 
     /// Number of blocks to start scanning a database
     #[clap(long, default_value_t = 100)]
@@ -37,53 +53,403 @@ struct Args {
     #[clap(long, default_value_t = 5000)]
     scan_time_limit: u16,
 
-    /// EVM pallet support
+    /// Maximum time limit (ms) a client can request with `timeLimitMs`
+    #[clap(long, default_value_t = 30000)]
+    max_scan_time_limit: u16,
+
+    /// Size (bytes) of serialized items after which a batch response ends
+    #[clap(long, default_value_t = 1024 * 1024)]
+    response_size_limit: usize,
+
+    /// Maximum size (bytes) of a batch response a client can request with `maxBytes`
+    #[clap(long, default_value_t = 10 * 1024 * 1024)]
+    max_response_size_limit: usize,
+
+    /// Number of blocks behind the head after which blocks are reported as finalized,
+    /// all ingested blocks are considered finalized if not set
     #[clap(long)]
+    confirmation_depth: Option<u32>,
+
+    /// Time (seconds) in-flight requests have to complete after SIGTERM
+    #[clap(long, default_value_t = 30)]
+    shutdown_grace_period: u64,
+
+    /// Maximum nesting of fields in a query, 0 disables the limit
+    #[clap(long, default_value_t = 0)]
+    max_query_depth: usize,
+
+    /// Maximum number of fields in a query, 0 disables the limit
+    #[clap(long, default_value_t = 0)]
+    max_query_complexity: usize,
+
+    /// Maximum number of selections in a batch query, 0 disables the limit
+    #[clap(long, default_value_t = 0)]
+    max_selections: usize,
+
+    /// Maximum number of topics in EVM log filters of a batch query, 0 disables the limit
+    #[clap(long, default_value_t = 0)]
+    max_topics: usize,
+
+    /// Response compression level from 1 (fastest) to 9 (best), 0 disables compression
+    #[clap(long, default_value_t = 4)]
+    compression_level: u32,
+
+    /// Minimum size (bytes) of a response to be compressed
+    #[clap(long, default_value_t = 1024)]
+    compression_min_size: usize,
+
+    /// EVM pallet support
+    #[clap(long, global = true)]
     evm_support: bool,
 
     /// Сontracts pallet support
-    #[clap(long)]
+    #[clap(long, global = true)]
     contracts_support: bool,
 
     /// Gear pallet support
-    #[clap(long)]
+    #[clap(long, global = true)]
     gear_support: bool,
 
     /// Acala's EVM pallet support
-    #[clap(long)]
+    #[clap(long, global = true)]
     acala_support: bool,
+
+    /// JSON file mapping sha256 hashes to queries a client can run by the hash
+    #[clap(long)]
+    persisted_queries: Option<String>,
+
+    /// Reject queries which aren't among persisted queries
+    #[clap(long, requires = "persisted-queries")]
+    persisted_queries_only: bool,
+
+    /// Number of queries remembered for automatic persisted queries, 0 disables the cache
+    #[clap(long, default_value_t = 1000)]
+    apq_cache_size: usize,
+
+    /// Origin allowed to query `/graphql` from a browser, `*` allows any origin
+    #[clap(long = "cors-allowed-origin")]
+    cors_allowed_origins: Vec<String>,
+
+    /// Method allowed in cross-origin requests
+    #[clap(long = "cors-allowed-method", default_values = &["POST"])]
+    cors_allowed_methods: Vec<String>,
+
+    /// Header allowed in cross-origin requests
+    #[clap(
+        long = "cors-allowed-header",
+        default_values = &["content-type", "x-squid-processor", "x-request-id"]
+    )]
+    cors_allowed_headers: Vec<String>,
+
+    /// Time (seconds) a browser may cache a preflight response
+    #[clap(long, default_value_t = 3600)]
+    cors_max_age: usize,
+
+    /// PEM file with the certificate chain to serve https, reloaded on SIGHUP
+    #[clap(long, requires = "tls-key")]
+    tls_cert: Option<PathBuf>,
+
+    /// PEM file with the private key of the certificate, reloaded on SIGHUP
+    #[clap(long, requires = "tls-cert")]
+    tls_key: Option<PathBuf>,
+
+    /// PEM file with CA certificates client certificates are verified against
+    #[clap(long, requires = "tls-cert")]
+    tls_client_ca: Option<PathBuf>,
+
+    /// Reject clients which don't present a certificate
+    #[clap(long, requires = "tls-client-ca")]
+    tls_client_cert_required: bool,
+
+    /// Page served on `/`, the playground unless in production mode
+    #[clap(long, value_enum)]
+    ui: Option<UiPage>,
+
+    /// Reject introspection queries
+    #[clap(long)]
+    disable_introspection: bool,
+
+    /// Serve the info page on `/` unless `--ui` is set
+    #[clap(long)]
+    production: bool,
+
+    /// Bearer token enabling `/admin` endpoints
+    #[clap(long)]
+    admin_token: Option<String>,
+
+    /// Disable a pallet support if the database lacks its tables or indexes instead of refusing to start
+    #[clap(long)]
+    disable_unsupported_features: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Print the schema served with the given support flags
+    Schema {
+        /// Output format
+        #[clap(long, value_enum, default_value_t = SchemaFormat::Sdl)]
+        format: SchemaFormat,
+    },
+    /// Export events, calls and extrinsics of a block range to files
+    Export(ExportArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct ExportArgs {
+    /// Directory to write files to, an export found there is resumed
+    #[clap(long)]
+    output: PathBuf,
+
+    /// First block to export
+    #[clap(long, default_value_t = 0)]
+    from_block: i32,
+
+    /// Last block to export, the archive head if not set
+    #[clap(long)]
+    to_block: Option<i32>,
+
+    /// Name of events to export, `*` for all events
+    #[clap(long = "event")]
+    events: Vec<String>,
+
+    /// Name of calls to export, `*` for all calls
+    #[clap(long = "call")]
+    calls: Vec<String>,
+
+    /// Output format
+    #[clap(long, value_enum, default_value_t = FileFormat::Parquet)]
+    format: FileFormat,
+
+    /// Maximum number of rows in a parquet row group
+    #[clap(long, default_value_t = 100_000)]
+    row_group_size: usize,
+
+    /// Number of blocks covered by a single file
+    #[clap(long, default_value_t = 100_000)]
+    blocks_per_file: u32,
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+enum SchemaFormat {
+    /// GraphQL SDL
+    Sdl,
+    /// JSON Schema of items returned by the batch query
+    BatchJsonSchema,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum FileFormat {
+    Parquet,
+    Csv,
+}
+
+impl From<FileFormat> for ExportFormat {
+    fn from(format: FileFormat) -> ExportFormat {
+        match format {
+            FileFormat::Parquet => ExportFormat::Parquet,
+            FileFormat::Csv => ExportFormat::Csv,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+enum UiPage {
+    /// GraphQL Playground
+    Playground,
+    /// GraphiQL
+    Graphiql,
+    /// Static page with the gateway version and enabled pallet supports
+    Info,
+}
+
+impl From<UiPage> for Ui {
+    fn from(page: UiPage) -> Ui {
+        match page {
+            UiPage::Playground => Ui::Playground,
+            UiPage::Graphiql => Ui::Graphiql,
+            UiPage::Info => Ui::Info,
+        }
+    }
 }
 
 #[tracing::instrument]
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     let args = Args::parse();
+    if let Some(Command::Schema { format }) = &args.command {
+        print_schema(&args, format).await;
+        return Ok(());
+    }
     logger::init();
+    if let Some(Command::Export(export_args)) = &args.command {
+        return run_export(&args, export_args).await;
+    }
+
+    let pool = connect(&args)?;
+    let mut gateway = match args.database_type {
+        Some(database_type) => SubstrateGateway::new(pool, database_type),
+        None => SubstrateGateway::with_detected_database_type(pool),
+    };
+    if let Some(token) = &args.admin_token {
+        gateway = gateway.admin_token(token.clone());
+    }
+    if let Some(path) = &args.index_hints {
+        let index_hints = IndexHints::from_file(path)
+            .map_err(|err| std::io::Error::other(format!("invalid index hints: {}", err)))?;
+        gateway = gateway.index_hints(index_hints);
+    }
+    if let Some(depth) = args.confirmation_depth {
+        gateway = gateway.confirmation_depth(depth);
+    }
+    if let (Some(cert), Some(key)) = (&args.tls_cert, &args.tls_key) {
+        gateway = gateway.tls(cert.clone(), key.clone());
+    }
+    if let Some(path) = &args.tls_client_ca {
+        gateway = gateway
+            .tls_client_ca(path.clone())
+            .tls_client_cert_required(args.tls_client_cert_required);
+    }
+    if args.production {
+        gateway = gateway.production();
+    }
+    if let Some(ui) = args.ui {
+        gateway = gateway.ui(ui.into());
+    }
+    if args.disable_introspection {
+        gateway = gateway.disable_introspection(true);
+    }
+    if let Some(path) = &args.persisted_queries {
+        let persisted_queries = PersistedQueries::from_file(path)
+            .map_err(|err| std::io::Error::other(format!("invalid persisted queries: {}", err)))?;
+        tracing::info!(
+            count = persisted_queries.len(),
+            only = args.persisted_queries_only,
+            "persisted queries loaded"
+        );
+        gateway = gateway.persisted_queries(persisted_queries);
+    }
+    gateway
+        .evm_support(args.evm_support)
+        .contracts_support(args.contracts_support)
+        .gear_support(args.gear_support)
+        .acala_support(args.acala_support)
+        .disable_unsupported_features(args.disable_unsupported_features)
+        .scan_start_value(args.scan_start_value)
+        .scan_max_value(args.scan_max_value)
+        .scan_time_limit(args.scan_time_limit)
+        .max_scan_time_limit(args.max_scan_time_limit)
+        .response_size_limit(args.response_size_limit)
+        .max_response_size_limit(args.max_response_size_limit)
+        .shutdown_grace_period(args.shutdown_grace_period)
+        .compression_level(args.compression_level)
+        .compression_min_size(args.compression_min_size)
+        .persisted_queries_only(args.persisted_queries_only)
+        .apq_cache_size(args.apq_cache_size)
+        .max_query_depth(args.max_query_depth)
+        .max_query_complexity(args.max_query_complexity)
+        .max_selections(args.max_selections)
+        .max_topics(args.max_topics)
+        .cors_allowed_origins(args.cors_allowed_origins.clone())
+        .cors_allowed_methods(args.cors_allowed_methods.clone())
+        .cors_allowed_headers(args.cors_allowed_headers.clone())
+        .cors_max_age(args.cors_max_age)
+        .run()
+        .await
+}
 
+async fn print_schema(args: &Args, format: &SchemaFormat) {
+    match format {
+        SchemaFormat::Sdl => {
+            let sdl = SubstrateGateway::in_memory(MemoryArchive::default())
+                .evm_support(args.evm_support)
+                .contracts_support(args.contracts_support)
+                .gear_support(args.gear_support)
+                .acala_support(args.acala_support)
+                .sdl()
+                .await;
+            print!("{}", sdl);
+        }
+        SchemaFormat::BatchJsonSchema => {
+            let schema = substrate_archive::json_schema::batch();
+            println!("{}", serde_json::to_string_pretty(&schema).unwrap());
+        }
+    }
+}
+
+fn connect(args: &Args) -> std::io::Result<Pool<Postgres>> {
+    let database_url = args
+        .database_url
+        .as_ref()
+        .ok_or_else(|| std::io::Error::other("--database-url is required"))?;
+    let statement_timeout = args.database_statement_timeout;
     let pool = PgPoolOptions::new()
         .max_connections(args.database_max_connections)
         .idle_timeout(Duration::from_secs(10))
         .acquire_timeout(Duration::from_secs(5))
         .after_connect(move |connection, _meta| {
             Box::pin(async move {
-                let query = format!(
-                    "SET statement_timeout = {}",
-                    args.database_statement_timeout
-                );
+                let query = format!("SET statement_timeout = {}", statement_timeout);
                 connection.execute(&*query).await?;
                 Ok(())
             })
         })
-        .connect_lazy(&args.database_url)
-        .unwrap();
-    SubstrateGateway::new(pool, args.database_type)
-        .evm_support(args.evm_support)
-        .contracts_support(args.contracts_support)
-        .gear_support(args.gear_support)
-        .acala_support(args.acala_support)
-        .scan_start_value(args.scan_start_value)
-        .scan_max_value(args.scan_max_value)
-        .scan_time_limit(args.scan_time_limit)
-        .run()
-        .await
+        .connect_lazy(database_url)
+        .map_err(|err| std::io::Error::other(err.to_string()))?;
+    Ok(pool)
+}
+
+async fn run_export(args: &Args, export_args: &ExportArgs) -> std::io::Result<()> {
+    let pool = connect(args)?;
+    let database_type = match &args.database_type {
+        Some(database_type) => database_type.clone(),
+        None => {
+            let (database_type, _) = DatabaseType::detect(&pool)
+                .await
+                .map_err(|err| std::io::Error::other(err.to_string()))?;
+            database_type
+        }
+    };
+    let mut archive = PostgresArchive::new(
+        pool.clone(),
+        database_type,
+        args.scan_start_value,
+        args.scan_max_value,
+        args.scan_time_limit,
+    );
+    if let Some(path) = &args.index_hints {
+        let index_hints = IndexHints::from_file(path)
+            .map_err(|err| std::io::Error::other(format!("invalid index hints: {}", err)))?;
+        archive = archive.index_hints(index_hints);
+    }
+    let selections = Selections {
+        event: export_args
+            .events
+            .iter()
+            .map(|name| EventSelection {
+                name: name.clone(),
+                data: EventDataSelection::new(true),
+            })
+            .collect(),
+        call: export_args
+            .calls
+            .iter()
+            .map(|name| CallSelection {
+                name: name.clone(),
+                data: CallDataSelection::new(true),
+            })
+            .collect(),
+        ..Default::default()
+    };
+    let options = ExportOptions {
+        from_block: export_args.from_block,
+        to_block: export_args.to_block,
+        selections,
+        output: export_args.output.clone(),
+        format: export_args.format.into(),
+        row_group_size: export_args.row_group_size,
+        blocks_per_file: export_args.blocks_per_file,
+    };
+    let result = export(&archive, &options).await;
+    pool.close().await;
+    result
 }
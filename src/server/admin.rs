@@ -0,0 +1,270 @@
+use crate::graphql::BatchActivity;
+use actix_web::dev::Service;
+use actix_web::guard::{Delete, Get};
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::web::{delete, post, resource, scope, Data, Path, ServiceConfig};
+use actix_web::HttpResponse;
+use futures_util::future::{ready, AbortHandle, Either};
+use futures_util::TryFutureExt;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use substrate_archive::archive::Selections;
+use tracing::info;
+
+struct Query {
+    request_id: String,
+    processor: Option<String>,
+    started: Instant,
+    activity: Arc<Mutex<Option<BatchActivity>>>,
+    abort: AbortHandle,
+}
+
+/// GraphQL requests being processed by the gateway.
+///
+/// Requests are keyed by an id assigned by the gateway as request ids
+/// sent by clients aren't guaranteed to be unique.
+#[derive(Default)]
+pub struct Queries {
+    running: Mutex<HashMap<u64, Query>>,
+    next_id: AtomicU64,
+    draining: AtomicBool,
+}
+
+impl Queries {
+    /// Tracks a request until the returned registration is dropped.
+    pub fn register(
+        queries: &Data<Queries>,
+        request_id: String,
+        processor: Option<String>,
+        activity: Arc<Mutex<Option<BatchActivity>>>,
+        abort: AbortHandle,
+    ) -> Registration {
+        let id = queries.next_id.fetch_add(1, Ordering::Relaxed);
+        let query = Query {
+            request_id,
+            processor,
+            started: Instant::now(),
+            activity,
+            abort,
+        };
+        queries.running.lock().unwrap().insert(id, query);
+        Registration {
+            queries: queries.clone(),
+            id,
+        }
+    }
+
+    /// New requests are rejected in drain mode, running ones are completed.
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::SeqCst)
+    }
+
+    pub fn set_draining(&self, value: bool) {
+        self.draining.store(value, Ordering::SeqCst);
+    }
+
+    /// Number of requests being processed
+    pub fn running(&self) -> usize {
+        self.running.lock().unwrap().len()
+    }
+
+    /// Request ids of running requests
+    pub fn request_ids(&self) -> Vec<String> {
+        self.running
+            .lock()
+            .unwrap()
+            .values()
+            .map(|query| query.request_id.clone())
+            .collect()
+    }
+
+    /// Aborts all running requests
+    pub fn abort_all(&self) {
+        for query in self.running.lock().unwrap().values() {
+            query.abort.abort();
+        }
+    }
+
+    fn abort(&self, id: u64) -> bool {
+        match self.running.lock().unwrap().get(&id) {
+            Some(query) => {
+                query.abort.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn status(&self) -> Value {
+        let running = self.running.lock().unwrap();
+        let mut queries: Vec<(&u64, &Query)> = running.iter().collect();
+        queries.sort_by_key(|(_, query)| query.started);
+        let queries: Vec<Value> = queries
+            .into_iter()
+            .map(|(id, query)| {
+                let mut value = json!({
+                    "id": id,
+                    "requestId": query.request_id,
+                    "processor": query.processor,
+                    "elapsedMs": query.started.elapsed().as_millis() as u64,
+                });
+                if let Some(activity) = &*query.activity.lock().unwrap() {
+                    value["fromBlock"] = activity.from_block.into();
+                    value["toBlock"] = activity.to_block.into();
+                    value["scanPosition"] = activity.progress.get().into();
+                    value["selections"] = summary(&activity.selections);
+                }
+                value
+            })
+            .collect();
+        json!({
+            "draining": self.is_draining(),
+            "queries": queries,
+        })
+    }
+}
+
+pub struct Registration {
+    queries: Data<Queries>,
+    id: u64,
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        self.queries.running.lock().unwrap().remove(&self.id);
+    }
+}
+
+// names of requested items grouped the same way as arguments of the batch query
+fn summary(selections: &Selections) -> Value {
+    let groups: [(&str, Vec<&String>); 9] = [
+        ("events", selections.event.iter().map(|s| &s.name).collect()),
+        ("calls", selections.call.iter().map(|s| &s.name).collect()),
+        (
+            "evmLogs",
+            selections.evm_log.iter().map(|s| &s.contract).collect(),
+        ),
+        (
+            "ethereumTransactions",
+            selections
+                .eth_transact
+                .iter()
+                .map(|s| &s.contract)
+                .collect(),
+        ),
+        (
+            "contractsEvents",
+            selections
+                .contracts_event
+                .iter()
+                .map(|s| &s.contract)
+                .collect(),
+        ),
+        (
+            "gearMessagesEnqueued",
+            selections
+                .gear_message_enqueued
+                .iter()
+                .map(|s| &s.program)
+                .collect(),
+        ),
+        (
+            "gearUserMessagesSent",
+            selections
+                .gear_user_message_sent
+                .iter()
+                .map(|s| &s.program)
+                .collect(),
+        ),
+        (
+            "acalaEvmExecuted",
+            selections
+                .acala_evm_executed
+                .iter()
+                .map(|s| &s.contract)
+                .collect(),
+        ),
+        (
+            "acalaEvmExecutedFailed",
+            selections
+                .acala_evm_executed_failed
+                .iter()
+                .map(|s| &s.contract)
+                .collect(),
+        ),
+    ];
+    let summary = groups
+        .into_iter()
+        .filter(|(_, items)| !items.is_empty())
+        .map(|(name, items)| (name.to_string(), json!(items)))
+        .collect();
+    Value::Object(summary)
+}
+
+async fn list_queries(queries: Data<Queries>) -> HttpResponse {
+    HttpResponse::Ok().json(queries.status())
+}
+
+async fn abort_query(queries: Data<Queries>, id: Path<u64>) -> HttpResponse {
+    if queries.abort(*id) {
+        info!(id = *id, "query aborted by admin");
+        HttpResponse::NoContent().finish()
+    } else {
+        HttpResponse::NotFound().finish()
+    }
+}
+
+async fn start_draining(queries: Data<Queries>) -> HttpResponse {
+    queries.set_draining(true);
+    info!("drain mode enabled");
+    HttpResponse::Ok().json(queries.status())
+}
+
+async fn stop_draining(queries: Data<Queries>) -> HttpResponse {
+    queries.set_draining(false);
+    info!("drain mode disabled");
+    HttpResponse::Ok().json(queries.status())
+}
+
+// compares in constant time so that a token can't be guessed byte by byte
+fn token_matches(given: &[u8], expected: &[u8]) -> bool {
+    given.len() == expected.len()
+        && given
+            .iter()
+            .zip(expected)
+            .fold(0, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+/// Endpoints under `/admin` available with `Authorization: Bearer <token>`.
+pub fn config(token: String) -> impl FnOnce(&mut ServiceConfig) {
+    move |cfg| {
+        cfg.service(
+            scope("/admin")
+                .wrap_fn(move |req, srv| {
+                    let authorized = req
+                        .headers()
+                        .get(AUTHORIZATION)
+                        .and_then(|value| value.to_str().ok())
+                        .and_then(|value| value.strip_prefix("Bearer "))
+                        .is_some_and(|given| token_matches(given.as_bytes(), token.as_bytes()));
+                    if authorized {
+                        Either::Left(srv.call(req).map_ok(|res| res.map_into_left_body()))
+                    } else {
+                        let res = req.into_response(HttpResponse::Unauthorized().finish());
+                        Either::Right(ready(Ok(res.map_into_right_body())))
+                    }
+                })
+                .service(resource("/queries").guard(Get()).to(list_queries))
+                .service(resource("/queries/{id}").guard(Delete()).to(abort_query))
+                .service(
+                    resource("/drain")
+                        .route(post().to(start_draining))
+                        .route(delete().to(stop_draining)),
+                ),
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use actix_web::body::{to_bytes, BoxBody, MessageBody};
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::error::{ErrorInternalServerError, ErrorServiceUnavailable};
+use actix_web::http::header::{self, HeaderMap, HeaderValue};
+use actix_web::web::block;
+use actix_web::Error;
+use futures_util::future::LocalBoxFuture;
+use std::future::{ready, Ready};
+use std::io::Write;
+
+/// Content codings in the order of preference when a client accepts several equally.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    Zstd,
+    Brotli,
+    Gzip,
+}
+
+const PREFERENCE: [Encoding; 3] = [Encoding::Zstd, Encoding::Brotli, Encoding::Gzip];
+
+impl Encoding {
+    fn from_name(name: &str) -> Option<Encoding> {
+        match name {
+            "zstd" => Some(Encoding::Zstd),
+            "br" => Some(Encoding::Brotli),
+            "gzip" | "x-gzip" => Some(Encoding::Gzip),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Encoding::Zstd => "zstd",
+            Encoding::Brotli => "br",
+            Encoding::Gzip => "gzip",
+        }
+    }
+
+    fn encode(&self, data: &[u8], level: u32) -> std::io::Result<Vec<u8>> {
+        match self {
+            Encoding::Zstd => zstd::stream::encode_all(data, level as i32),
+            Encoding::Brotli => {
+                let mut encoder = brotli::CompressorWriter::new(Vec::new(), 4096, level, 22);
+                encoder.write_all(data)?;
+                Ok(encoder.into_inner())
+            }
+            Encoding::Gzip => {
+                let compression = flate2::Compression::new(level);
+                let mut encoder = flate2::write::GzEncoder::new(Vec::new(), compression);
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+        }
+    }
+}
+
+/// Picks an encoding with the highest quality value from `Accept-Encoding`.
+pub fn negotiate(headers: &HeaderMap) -> Option<Encoding> {
+    let mut qualities = [None; 3];
+    let mut wildcard = None;
+    for value in headers.get_all(header::ACCEPT_ENCODING) {
+        let value = match value.to_str() {
+            Ok(value) => value,
+            Err(..) => continue,
+        };
+        for item in value.split(',') {
+            let mut parts = item.split(';').map(str::trim);
+            let name = parts.next().unwrap_or_default().to_ascii_lowercase();
+            let quality = parts
+                .find_map(|param| param.strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.parse::<f32>().ok());
+            let quality = match quality {
+                Some(quality) => quality,
+                None => continue,
+            };
+            if name == "*" {
+                wildcard = Some(quality);
+            } else if let Some(encoding) = Encoding::from_name(&name) {
+                let index = PREFERENCE.iter().position(|e| *e == encoding).unwrap();
+                qualities[index] = Some(quality);
+            }
+        }
+    }
+    PREFERENCE
+        .iter()
+        .zip(qualities)
+        .filter_map(|(encoding, quality)| quality.or(wildcard).map(|q| (*encoding, q)))
+        .filter(|(_, quality)| *quality > 0.0)
+        // the first of equally preferred encodings wins
+        .fold(
+            None,
+            |best: Option<(Encoding, f32)>, (encoding, quality)| match best {
+                Some((_, best_quality)) if best_quality >= quality => best,
+                _ => Some((encoding, quality)),
+            },
+        )
+        .map(|(encoding, _)| encoding)
+}
+
+/// Compresses responses of at least `min_size` bytes with the encoding
+/// negotiated with a client.
+#[derive(Clone, Copy)]
+pub struct Compress {
+    /// Compression level from 1 (fastest) to 9 (best), 0 disables compression
+    pub level: u32,
+    pub min_size: usize,
+}
+
+impl<S, B> Transform<S, ServiceRequest> for Compress
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = CompressMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(CompressMiddleware {
+            service,
+            config: *self,
+        }))
+    }
+}
+
+pub struct CompressMiddleware<S> {
+    service: S,
+    config: Compress,
+}
+
+impl<S, B> Service<ServiceRequest> for CompressMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let encoding = if self.config.level > 0 {
+            negotiate(req.headers())
+        } else {
+            None
+        };
+        let level = self.config.level.min(9);
+        let min_size = self.config.min_size;
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let res = fut.await?;
+            let encoding = match encoding {
+                Some(encoding) if !res.headers().contains_key(header::CONTENT_ENCODING) => encoding,
+                _ => return Ok(res.map_into_boxed_body()),
+            };
+            let (req, res) = res.into_parts();
+            let (mut res, body) = res.into_parts();
+            let body = to_bytes(body)
+                .await
+                .map_err(|err| ErrorInternalServerError(err.into().to_string()))?;
+            res.headers_mut()
+                .append(header::VARY, HeaderValue::from_static("accept-encoding"));
+            if body.len() < min_size {
+                let res = res.set_body(body).map_into_boxed_body();
+                return Ok(ServiceResponse::new(req, res));
+            }
+            let compressed = block(move || encoding.encode(&body, level))
+                .await
+                .map_err(|err| ErrorServiceUnavailable(err.to_string()))?
+                .map_err(ErrorInternalServerError)?;
+            res.headers_mut().insert(
+                header::CONTENT_ENCODING,
+                HeaderValue::from_static(encoding.name()),
+            );
+            let res = res.set_body(compressed).map_into_boxed_body();
+            Ok(ServiceResponse::new(req, res))
+        })
+    }
+}
@@ -0,0 +1,70 @@
+use actix_cors::Cors;
+use actix_web::http::header::HeaderName;
+use actix_web::http::{Method, Uri};
+use std::io::Error;
+
+/// Cross-origin access to `/graphql` for browser based clients.
+#[derive(Clone, Debug)]
+pub struct CorsConfig {
+    /// Origins allowed to send requests, `*` allows any origin, CORS is off if empty
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    pub allowed_headers: Vec<String>,
+    /// Time (seconds) a browser may cache a preflight response
+    pub max_age: usize,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        CorsConfig {
+            allowed_origins: vec![],
+            allowed_methods: vec!["POST".to_string()],
+            allowed_headers: vec![
+                "content-type".to_string(),
+                "x-squid-processor".to_string(),
+                "x-request-id".to_string(),
+            ],
+            max_age: 3600,
+        }
+    }
+}
+
+impl CorsConfig {
+    pub fn is_enabled(&self) -> bool {
+        !self.allowed_origins.is_empty()
+    }
+
+    // `Cors` fails to start a worker on invalid values, so they are reported beforehand
+    pub fn validate(&self) -> Result<(), Error> {
+        for origin in &self.allowed_origins {
+            if origin != "*" && Uri::try_from(origin.as_str()).is_err() {
+                return Err(Error::other(format!("invalid CORS origin: {}", origin)));
+            }
+        }
+        for method in &self.allowed_methods {
+            if Method::from_bytes(method.as_bytes()).is_err() {
+                return Err(Error::other(format!("invalid CORS method: {}", method)));
+            }
+        }
+        for header in &self.allowed_headers {
+            if HeaderName::from_bytes(header.as_bytes()).is_err() {
+                return Err(Error::other(format!("invalid CORS header: {}", header)));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn cors(&self) -> Cors {
+        let mut cors = Cors::default();
+        for origin in &self.allowed_origins {
+            cors = if origin == "*" {
+                cors.allow_any_origin()
+            } else {
+                cors.allowed_origin(origin)
+            };
+        }
+        cors.allowed_methods(self.allowed_methods.iter().map(String::as_str))
+            .allowed_headers(self.allowed_headers.iter().map(String::as_str))
+            .max_age(self.max_age)
+    }
+}
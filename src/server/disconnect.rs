@@ -0,0 +1,54 @@
+use actix_tls::accept::rustls::TlsStream;
+use actix_web::dev::Extensions;
+use actix_web::rt::net::TcpStream;
+use actix_web::rt::time::sleep;
+use socket2::{SockRef, Socket};
+use std::any::Any;
+use std::io::ErrorKind;
+use std::mem::MaybeUninit;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Socket of a client connection used to notice the client is gone
+/// while its request is being processed.
+#[derive(Clone)]
+pub struct ClientSocket(Arc<Socket>);
+
+impl ClientSocket {
+    fn is_closed(&self) -> bool {
+        let mut buf = [MaybeUninit::<u8>::uninit(); 1];
+        match self.0.peek(&mut buf) {
+            // end of stream, the client has closed the connection
+            Ok(0) => true,
+            // a pipelined request is waiting
+            Ok(_) => false,
+            Err(err) => err.kind() != ErrorKind::WouldBlock && err.kind() != ErrorKind::Interrupted,
+        }
+    }
+
+    /// Resolves once the client closes the connection.
+    pub async fn closed(&self) {
+        while !self.is_closed() {
+            sleep(POLL_INTERVAL).await;
+        }
+    }
+}
+
+/// Stores a handle of the connection socket for request handlers.
+pub fn on_connect(conn: &dyn Any, data: &mut Extensions) {
+    let stream = match conn.downcast_ref::<TlsStream<TcpStream>>() {
+        Some(stream) => Some(stream.get_ref().0),
+        None => conn.downcast_ref::<TcpStream>(),
+    };
+    if let Some(stream) = stream {
+        match SockRef::from(stream).try_clone() {
+            Ok(socket) => {
+                data.insert(ClientSocket(Arc::new(socket)));
+            }
+            Err(err) => warn!(error = err.to_string().as_str(), "failed to clone a socket"),
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use actix_web::http::header::{self, HeaderMap};
+use actix_web::HttpResponse;
+use async_graphql::Response;
+
+/// Binary representations of a GraphQL response a client can ask for
+/// with the `Accept` header instead of JSON.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    MessagePack,
+    Cbor,
+}
+
+impl Format {
+    fn from_media_type(media_type: &str) -> Option<Format> {
+        match media_type {
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => {
+                Some(Format::MessagePack)
+            }
+            "application/cbor" => Some(Format::Cbor),
+            _ => None,
+        }
+    }
+
+    fn content_type(&self) -> &'static str {
+        match self {
+            Format::MessagePack => "application/msgpack",
+            Format::Cbor => "application/cbor",
+        }
+    }
+
+    fn encode(&self, response: &Response) -> Result<Vec<u8>, String> {
+        match self {
+            Format::MessagePack => rmp_serde::to_vec_named(response).map_err(|e| e.to_string()),
+            Format::Cbor => {
+                let mut buf = vec![];
+                ciborium::ser::into_writer(response, &mut buf).map_err(|e| e.to_string())?;
+                Ok(buf)
+            }
+        }
+    }
+
+    /// Serializes the response in the format with `200 OK` as it's done for JSON.
+    pub fn respond(&self, response: &Response) -> HttpResponse {
+        match self.encode(response) {
+            Ok(body) => HttpResponse::Ok()
+                .content_type(self.content_type())
+                .body(body),
+            Err(err) => HttpResponse::InternalServerError().body(err),
+        }
+    }
+}
+
+/// Picks a binary format the client prefers over JSON, if any.
+///
+/// Only explicitly listed media types are considered, so wildcards keep JSON.
+pub fn negotiate(headers: &HeaderMap) -> Option<Format> {
+    let mut best: Option<(Option<Format>, f32)> = None;
+    for value in headers.get_all(header::ACCEPT) {
+        let value = match value.to_str() {
+            Ok(value) => value,
+            Err(..) => continue,
+        };
+        for item in value.split(',') {
+            let mut parts = item.split(';').map(str::trim);
+            let media_type = parts.next().unwrap_or_default().to_ascii_lowercase();
+            let quality = match parts
+                .find_map(|param| param.strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.parse::<f32>().ok())
+            {
+                Some(quality) if quality > 0.0 => quality,
+                _ => continue,
+            };
+            let format = match media_type.as_str() {
+                "application/json" | "application/graphql-response+json" | "*/*" => None,
+                media_type => match Format::from_media_type(media_type) {
+                    Some(format) => Some(format),
+                    None => continue,
+                },
+            };
+            // the first of equally weighted types wins
+            if best.is_none_or(|(_, best_quality)| quality > best_quality) {
+                best = Some((format, quality));
+            }
+        }
+    }
+    best.and_then(|(format, _)| format)
+}
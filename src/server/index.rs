@@ -0,0 +1,43 @@
+use async_graphql::http::{playground_source, GraphQLPlaygroundConfig, GraphiQLSource};
+
+/// Page served on `/`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Ui {
+    /// GraphQL Playground
+    Playground,
+    /// GraphiQL
+    Graphiql,
+    /// Static page with the gateway version and enabled pallet supports
+    Info,
+}
+
+/// Renders html of the page, `supports` are names of enabled pallet supports.
+pub fn page(ui: Ui, supports: &[&str]) -> String {
+    match ui {
+        Ui::Playground => playground_source(GraphQLPlaygroundConfig::new("/graphql")),
+        Ui::Graphiql => GraphiQLSource::build().endpoint("/graphql").finish(),
+        Ui::Info => {
+            let supports = if supports.is_empty() {
+                "none".to_string()
+            } else {
+                supports.join(", ")
+            };
+            format!(
+                concat!(
+                    "<!DOCTYPE html>\n",
+                    "<html>\n",
+                    "<head><meta charset=\"utf-8\"><title>Substrate Gateway</title></head>\n",
+                    "<body>\n",
+                    "<h1>Substrate Gateway</h1>\n",
+                    "<p>Version: {}</p>\n",
+                    "<p>Pallet supports: {}</p>\n",
+                    "<p>GraphQL endpoint: <code>POST /graphql</code></p>\n",
+                    "</body>\n",
+                    "</html>\n",
+                ),
+                env!("CARGO_PKG_VERSION"),
+                supports
+            )
+        }
+    }
+}
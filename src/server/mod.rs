@@ -1,33 +1,61 @@
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-use crate::graphql::{NextBlock, QueryRoot};
-use crate::metrics::{HTTP_REQUESTS_ERRORS, HTTP_REQUESTS_TOTAL, HTTP_RESPONSE_TIME_SECONDS};
+use crate::graphql::{self, QueryRoot};
+use crate::metrics::{
+    HTTP_REQUESTS_CANCELLED, HTTP_REQUESTS_ERRORS, HTTP_REQUESTS_TOTAL, HTTP_RESPONSE_TIME_SECONDS,
+};
 use actix_web::dev::Service;
-use actix_web::guard::{Get, Post};
+use actix_web::guard::Get;
 use actix_web::http::header::ContentType;
-use actix_web::web::{resource, Data};
-use actix_web::{App, HttpMessage, HttpRequest, HttpResponse, HttpServer, Result};
-use async_graphql::http::{playground_source, GraphQLPlaygroundConfig};
-use async_graphql::{EmptyMutation, EmptySubscription, Schema};
+use actix_web::middleware::Condition;
+use actix_web::web::{post, resource, Data};
+use actix_web::{App, Either, HttpMessage, HttpRequest, HttpResponse, HttpServer, Result};
+use admin::Queries;
+use async_graphql::{EmptyMutation, EmptySubscription, Response, Schema, ServerError};
 use async_graphql_actix_web::{GraphQLRequest, GraphQLResponse};
+pub use compression::Compress;
+pub use cors::CorsConfig;
+use disconnect::ClientSocket;
+use futures_util::future::{self, abortable, pending, select, Aborted};
+use futures_util::pin_mut;
+pub use index::Ui;
 use middleware::{BindRequestId, Logger, RequestId};
 use prometheus::{Encoder, TextEncoder};
-use tracing::{debug, error};
+pub use tls::TlsConfig;
+use tracing::{debug, error, info};
 
+mod admin;
+mod compression;
+mod cors;
+mod disconnect;
+mod format;
+pub mod index;
 mod middleware;
+mod shutdown;
+mod tls;
 
-async fn graphql_playground() -> Result<HttpResponse> {
-    let source = playground_source(GraphQLPlaygroundConfig::new("/graphql"));
+async fn index_page(page: Data<String>) -> Result<HttpResponse> {
     Ok(HttpResponse::Ok()
         .content_type("text/html; charset=utf-8")
-        .body(source))
+        .body(page.get_ref().clone()))
+}
+
+fn cancelled(message: &str) -> Either<GraphQLResponse, HttpResponse> {
+    let error = ServerError::new(message, None);
+    Either::Left(Response::from_errors(vec![error]).into())
 }
 
 async fn graphql_request(
     schema: Data<Schema<QueryRoot, EmptyMutation, EmptySubscription>>,
+    queries: Data<Queries>,
     req: HttpRequest,
     gql_req: GraphQLRequest,
-) -> GraphQLResponse {
+) -> Either<GraphQLResponse, HttpResponse> {
+    if queries.is_draining() {
+        let body = serde_json::json!({"errors": [{"message": "gateway is draining"}]});
+        return Either::Right(HttpResponse::ServiceUnavailable().json(body));
+    }
     let request_id = req
         .extensions()
         .get::<RequestId>()
@@ -44,10 +72,36 @@ async fn graphql_request(
         request_id,
         query = gql_req.0.query.as_str()
     );
-    let next_block = Arc::new(Mutex::new(NextBlock(None)));
-    let mut response = schema
-        .execute(gql_req.into_inner().data(next_block.clone()))
-        .await;
+    let activity = Arc::new(Mutex::new(None));
+    let request = gql_req.into_inner().data(activity.clone());
+    let (execution, abort) = abortable(graphql::execute(&schema, request));
+    let processor = x_squid_processor.map(String::from);
+    let _registration = Queries::register(&queries, request_id.clone(), processor, activity, abort);
+    let closed = async {
+        match req.conn_data::<ClientSocket>() {
+            Some(socket) => socket.closed().await,
+            None => pending().await,
+        }
+    };
+    pin_mut!(execution, closed);
+    // dropping the execution aborts its database queries
+    let response = match select(execution, closed).await {
+        future::Either::Left((Ok(response), _)) => response,
+        future::Either::Left((Err(Aborted), _)) => {
+            info!(x_squid_processor, request_id, "query aborted");
+            HTTP_REQUESTS_CANCELLED
+                .with_label_values(&["aborted"])
+                .inc();
+            return cancelled("query aborted");
+        }
+        future::Either::Right(..) => {
+            info!(x_squid_processor, request_id, "client disconnected");
+            HTTP_REQUESTS_CANCELLED
+                .with_label_values(&["disconnected"])
+                .inc();
+            return cancelled("client disconnected");
+        }
+    };
     if response.is_err() {
         for error in &response.errors {
             error!(
@@ -58,21 +112,18 @@ async fn graphql_request(
         }
         HTTP_REQUESTS_ERRORS.with_label_values(&[]).inc();
     }
-    let lock = next_block.lock().unwrap();
-    if let Some(next_block) = lock.0 {
-        let mut value = response.data.into_json().unwrap();
-        let data = value.as_object_mut().unwrap();
-        let batch = data.get("batch").unwrap();
-        data.insert(
-            "batch".to_string(),
-            serde_json::json!({
-                "data": batch,
-                "nextBlock": next_block
-            }),
-        );
-        response.data = serde_json::to_value(data).unwrap().try_into().unwrap();
+    match format::negotiate(req.headers()) {
+        Some(format) => Either::Right(format.respond(&response)),
+        None => Either::Left(response.into()),
+    }
+}
+
+async fn readiness(queries: Data<Queries>) -> HttpResponse {
+    if queries.is_draining() {
+        HttpResponse::ServiceUnavailable().body("draining")
+    } else {
+        HttpResponse::Ok().body("ok")
     }
-    response.into()
 }
 
 async fn metrics() -> Result<HttpResponse, actix_web::Error> {
@@ -90,17 +141,33 @@ async fn metrics() -> Result<HttpResponse, actix_web::Error> {
 
 pub async fn run(
     schema: Schema<QueryRoot, EmptyMutation, EmptySubscription>,
+    admin_token: Option<String>,
+    shutdown_grace_period: Duration,
+    compress: Compress,
+    index: String,
+    cors: CorsConfig,
+    tls: Option<TlsConfig>,
 ) -> std::io::Result<()> {
-    HttpServer::new(move || {
+    cors.validate()?;
+    let index = Data::new(index);
+    let queries = Data::new(Queries::default());
+    let app_queries = queries.clone();
+    let server = HttpServer::new(move || {
         App::new()
             .app_data(Data::new(schema.clone()))
+            .app_data(app_queries.clone())
             .wrap(Logger {})
             .wrap(BindRequestId {})
-            .service(resource("/").guard(Get()).to(graphql_playground))
+            .service(
+                resource("/")
+                    .guard(Get())
+                    .app_data(index.clone())
+                    .to(index_page),
+            )
             .service(
                 resource("/graphql")
-                    .guard(Post())
-                    .to(graphql_request)
+                    .route(post().to(graphql_request))
+                    .wrap(compress)
                     .wrap_fn(|req, srv| {
                         HTTP_REQUESTS_TOTAL.with_label_values(&[]).inc();
                         let timer = HTTP_RESPONSE_TIME_SECONDS
@@ -112,12 +179,35 @@ pub async fn run(
                             timer.observe_duration();
                             Ok(res)
                         }
-                    }),
+                    })
+                    // outermost, so that preflight requests aren't counted
+                    .wrap(Condition::new(cors.is_enabled(), cors.cors())),
             )
             .service(resource("/metrics").guard(Get()).to(metrics))
+            .service(resource("/ready").guard(Get()).to(readiness))
+            .configure(|cfg| {
+                if let Some(token) = &admin_token {
+                    admin::config(token.clone())(cfg);
+                }
+            })
     })
-    .bind("0.0.0.0:8000")
-    .unwrap()
-    .run()
-    .await
+    .on_connect(disconnect::on_connect)
+    .disable_signals();
+    let server = match &tls {
+        Some(tls) => {
+            let (config, resolver) = tls::server_config(tls)?;
+            actix_web::rt::spawn(tls::reload_on_sighup(resolver));
+            server.bind_rustls("0.0.0.0:8000", config)?
+        }
+        None => server.bind("0.0.0.0:8000")?,
+    }
+    .run();
+    actix_web::rt::spawn(shutdown::graceful(
+        server.handle(),
+        queries,
+        shutdown_grace_period,
+    ));
+    server.await?;
+    info!("http server stopped");
+    Ok(())
 }
@@ -0,0 +1,70 @@
+use super::admin::Queries;
+use actix_web::dev::ServerHandle;
+use actix_web::rt::signal::ctrl_c;
+use actix_web::rt::signal::unix::{signal, SignalKind};
+use actix_web::rt::time::sleep;
+use actix_web::web::Data;
+use futures_util::future::{select, Either};
+use futures_util::pin_mut;
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+// aborted requests normally stop right away, the server isn't held up by ones which don't
+const ABORT_TIMEOUT: Duration = Duration::from_secs(5);
+
+async fn wait_for_signal() -> std::io::Result<&'static str> {
+    let mut terminate = signal(SignalKind::terminate())?;
+    let terminate = terminate.recv();
+    let interrupt = ctrl_c();
+    pin_mut!(terminate, interrupt);
+    match select(terminate, interrupt).await {
+        Either::Left(..) => Ok("SIGTERM"),
+        Either::Right((result, _)) => result.map(|_| "SIGINT"),
+    }
+}
+
+/// Stops the server on SIGTERM or SIGINT.
+///
+/// New requests are rejected right away while running ones have
+/// `grace_period` to complete before they get aborted.
+pub async fn graceful(handle: ServerHandle, queries: Data<Queries>, grace_period: Duration) {
+    let signal = match wait_for_signal().await {
+        Ok(signal) => signal,
+        Err(err) => {
+            error!(
+                error = err.to_string().as_str(),
+                "failed to listen for signals"
+            );
+            return;
+        }
+    };
+    info!(signal, "shutdown started, new requests are rejected");
+    queries.set_draining(true);
+
+    let deadline = Instant::now() + grace_period;
+    while queries.running() > 0 && Instant::now() < deadline {
+        sleep(POLL_INTERVAL).await;
+    }
+    let running = queries.running();
+    if running == 0 {
+        info!("in-flight requests completed");
+    } else {
+        // aborted while workers are alive, so their statements get cancelled
+        warn!(running, "grace period elapsed, aborting in-flight requests");
+        queries.abort_all();
+        let deadline = Instant::now() + ABORT_TIMEOUT;
+        while queries.running() > 0 && Instant::now() < deadline {
+            sleep(POLL_INTERVAL).await;
+        }
+        let request_ids = queries.request_ids();
+        if !request_ids.is_empty() {
+            error!(
+                request_ids = request_ids.join(",").as_str(),
+                "aborted requests are still running, stopping anyway"
+            );
+        }
+    }
+
+    handle.stop(true).await;
+}
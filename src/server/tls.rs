@@ -0,0 +1,140 @@
+use actix_web::rt::signal::unix::{signal, SignalKind};
+use rustls::server::{
+    AllowAnyAnonymousOrAuthenticatedClient, AllowAnyAuthenticatedClient, ClientHello, NoClientAuth,
+    ResolvesServerCert,
+};
+use rustls::sign::{any_supported_type, CertifiedKey};
+use rustls::{Certificate, PrivateKey, RootCertStore, ServerConfig};
+use rustls_pemfile::Item;
+use std::fs::File;
+use std::io::{BufReader, Error};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use tracing::{error, info};
+
+/// Certificate and key of the server along with CA certificates
+/// client certificates are verified against.
+#[derive(Clone, Debug)]
+pub struct TlsConfig {
+    pub cert: PathBuf,
+    pub key: PathBuf,
+    /// Certificates presented by clients have to be issued by one of these CAs
+    pub client_ca: Option<PathBuf>,
+    /// Clients without a certificate are rejected
+    pub client_cert_required: bool,
+}
+
+fn read_pem(path: &Path) -> Result<Vec<Item>, Error> {
+    let mut reader = BufReader::new(File::open(path)?);
+    rustls_pemfile::read_all(&mut reader)
+        .map_err(|err| Error::other(format!("{}: {}", path.display(), err)))
+}
+
+fn load_certs(path: &Path) -> Result<Vec<Certificate>, Error> {
+    let certs: Vec<Certificate> = read_pem(path)?
+        .into_iter()
+        .filter_map(|item| match item {
+            Item::X509Certificate(der) => Some(Certificate(der)),
+            _ => None,
+        })
+        .collect();
+    if certs.is_empty() {
+        return Err(Error::other(format!("{}: no certificates", path.display())));
+    }
+    Ok(certs)
+}
+
+fn load_key(path: &Path) -> Result<PrivateKey, Error> {
+    read_pem(path)?
+        .into_iter()
+        .find_map(|item| match item {
+            Item::PKCS8Key(der) | Item::RSAKey(der) | Item::ECKey(der) => Some(PrivateKey(der)),
+            _ => None,
+        })
+        .ok_or_else(|| Error::other(format!("{}: no private key", path.display())))
+}
+
+impl TlsConfig {
+    fn certified_key(&self) -> Result<CertifiedKey, Error> {
+        let certs = load_certs(&self.cert)?;
+        let key = load_key(&self.key)?;
+        let key = any_supported_type(&key)
+            .map_err(|err| Error::other(format!("{}: {}", self.key.display(), err)))?;
+        Ok(CertifiedKey::new(certs, key))
+    }
+}
+
+/// Serves the current certificate, which can be replaced while the server is running.
+pub struct CertResolver {
+    config: TlsConfig,
+    current: RwLock<Arc<CertifiedKey>>,
+}
+
+impl CertResolver {
+    fn new(config: TlsConfig) -> Result<CertResolver, Error> {
+        let current = RwLock::new(Arc::new(config.certified_key()?));
+        Ok(CertResolver { config, current })
+    }
+
+    /// Reads the certificate and the key again, the current ones stay in use on failure.
+    fn reload(&self) -> Result<(), Error> {
+        let key = self.config.certified_key()?;
+        *self.current.write().unwrap() = Arc::new(key);
+        Ok(())
+    }
+}
+
+impl ResolvesServerCert for CertResolver {
+    fn resolve(&self, _client_hello: ClientHello) -> Option<Arc<CertifiedKey>> {
+        Some(self.current.read().unwrap().clone())
+    }
+}
+
+/// Builds rustls configuration of the server.
+pub fn server_config(config: &TlsConfig) -> Result<(ServerConfig, Arc<CertResolver>), Error> {
+    let verifier = match &config.client_ca {
+        Some(path) => {
+            let mut roots = RootCertStore::empty();
+            for cert in load_certs(path)? {
+                roots
+                    .add(&cert)
+                    .map_err(|err| Error::other(format!("{}: {}", path.display(), err)))?;
+            }
+            if config.client_cert_required {
+                AllowAnyAuthenticatedClient::new(roots)
+            } else {
+                AllowAnyAnonymousOrAuthenticatedClient::new(roots)
+            }
+        }
+        None => NoClientAuth::new(),
+    };
+    let resolver = Arc::new(CertResolver::new(config.clone())?);
+    let server_config = ServerConfig::builder()
+        .with_safe_defaults()
+        .with_client_cert_verifier(verifier)
+        .with_cert_resolver(resolver.clone());
+    Ok((server_config, resolver))
+}
+
+/// Reloads the certificate on every SIGHUP.
+pub async fn reload_on_sighup(resolver: Arc<CertResolver>) {
+    let mut hangup = match signal(SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(err) => {
+            error!(
+                error = err.to_string().as_str(),
+                "failed to listen for SIGHUP"
+            );
+            return;
+        }
+    };
+    while hangup.recv().await.is_some() {
+        match resolver.reload() {
+            Ok(()) => info!("tls certificate reloaded"),
+            Err(err) => error!(
+                error = err.to_string().as_str(),
+                "failed to reload tls certificate"
+            ),
+        }
+    }
+}
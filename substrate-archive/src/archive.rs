@@ -4,21 +4,80 @@ use super::selection::{
 };
 use crate::entities::{Batch, Metadata, Status};
 use crate::error::Error;
+use std::cmp::min;
+use std::sync::{Arc, Mutex};
 
 pub struct BatchOptions {
     pub limit: Option<i32>,
     pub from_block: i32,
     pub to_block: Option<i32>,
+    /// Position inside `from_block` to resume from, enables splitting of huge blocks
+    pub from_pos: Option<i64>,
+    /// Byte budget of the response, limited by the maximum of an archive
+    pub max_bytes: Option<usize>,
+    /// Time limit (ms) of a scan, limited by the maximum of an archive
+    pub time_limit_ms: Option<u32>,
+    /// Number of blocks a scan may cover
+    pub max_blocks: Option<u32>,
     pub include_all_blocks: bool,
+    /// Hash of the block preceding `from_block` known to a client,
+    /// the request fails with `Error::Reorg` if the archive has another one
+    pub parent_hash: Option<String>,
     pub selections: Selections,
+    /// Receives the block a scan has reached while the request is running
+    pub progress: Option<ScanProgress>,
+}
+
+impl BatchOptions {
+    /// Last block to scan up to, taking `max_blocks` into account.
+    pub fn scan_end(&self, to_block: i32) -> i32 {
+        match self.max_blocks {
+            Some(max_blocks) => {
+                let max_blocks = i32::try_from(max_blocks.max(1)).unwrap_or(i32::MAX);
+                min(to_block, self.from_block.saturating_add(max_blocks - 1))
+            }
+            None => to_block,
+        }
+    }
+
+    /// Compares `parent_hash` with `parents`, the parent hashes of blocks at `from_block`
+    /// and the hashes of blocks right before it, nothing is checked if they are unknown.
+    pub(crate) fn check_parent_hash(&self, parents: &[String]) -> Result<(), Error> {
+        match &self.parent_hash {
+            Some(parent_hash) if !parents.is_empty() && !parents.contains(parent_hash) => {
+                Err(Error::Reorg {
+                    height: self.from_block,
+                    expected: parent_hash.clone(),
+                    actual: parents[0].clone(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Block a running scan has reached, shared with observers of the request.
+#[derive(Clone, Default)]
+pub struct ScanProgress(Arc<Mutex<Option<i32>>>);
+
+impl ScanProgress {
+    pub fn set(&self, block: i32) {
+        *self.0.lock().unwrap() = Some(block);
+    }
+
+    pub fn get(&self) -> Option<i32> {
+        *self.0.lock().unwrap()
+    }
 }
 
 pub struct BatchResponse {
     pub data: Vec<Batch>,
     pub next_block: Option<i32>,
+    /// Position inside `next_block` to resume from if the block was split
+    pub next_pos: Option<i64>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct Selections {
     pub call: Vec<CallSelection>,
     pub event: Vec<EventSelection>,
@@ -31,6 +90,33 @@ pub struct Selections {
     pub acala_evm_executed_failed: Vec<AcalaEvmEventSelection>,
 }
 
+impl Selections {
+    /// Number of selections across all item types
+    pub fn count(&self) -> usize {
+        self.call.len()
+            + self.event.len()
+            + self.evm_log.len()
+            + self.eth_transact.len()
+            + self.contracts_event.len()
+            + self.gear_message_enqueued.len()
+            + self.gear_user_message_sent.len()
+            + self.acala_evm_executed.len()
+            + self.acala_evm_executed_failed.len()
+    }
+
+    /// Number of topics listed in filters of EVM logs
+    pub fn topic_count(&self) -> usize {
+        let evm_logs = self.evm_log.iter().flat_map(|s| &s.filter);
+        let acala_logs = self
+            .acala_evm_executed
+            .iter()
+            .chain(&self.acala_evm_executed_failed)
+            .flat_map(|s| &s.logs)
+            .flat_map(|log| &log.filter);
+        evm_logs.chain(acala_logs).map(|topics| topics.len()).sum()
+    }
+}
+
 #[async_trait::async_trait]
 pub trait ArchiveService {
     async fn batch(&self, options: &BatchOptions) -> Result<BatchResponse, Error>;
@@ -0,0 +1,70 @@
+use crate::entities::Batch;
+use serde_json::Value;
+use std::io;
+
+/// Byte budget of a `batch` response, measured over serialized items.
+#[derive(Debug, Clone, Copy)]
+pub struct ResponseSizeLimit {
+    /// Budget of a request which doesn't ask for its own one
+    pub default: usize,
+    /// Upper bound of a budget requested by a client
+    pub max: usize,
+}
+
+impl Default for ResponseSizeLimit {
+    fn default() -> ResponseSizeLimit {
+        ResponseSizeLimit {
+            default: 1024 * 1024,
+            max: 10 * 1024 * 1024,
+        }
+    }
+}
+
+impl ResponseSizeLimit {
+    pub fn resolve(&self, requested: Option<usize>) -> usize {
+        requested.unwrap_or(self.default).min(self.max)
+    }
+}
+
+// no serialized item is smaller, it has at least `id` and `pos`
+const MIN_ITEM_SIZE: usize = 32;
+
+/// Number of items which is always enough to exhaust the byte budget.
+pub fn rows_for(limit: usize) -> i64 {
+    i64::try_from(limit / MIN_ITEM_SIZE)
+        .unwrap_or(i64::MAX)
+        .max(1)
+}
+
+struct Counter(usize);
+
+impl io::Write for Counter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+pub fn size_of_value(value: &Value) -> usize {
+    let mut counter = Counter(0);
+    serde_json::to_writer(&mut counter, value).unwrap();
+    counter.0
+}
+
+// block headers are small and not taken into account
+pub fn size_of_batch(batch: &[Batch]) -> usize {
+    batch
+        .iter()
+        .flat_map(|item| {
+            item.extrinsics
+                .iter()
+                .chain(&item.calls)
+                .chain(&item.events)
+        })
+        .map(size_of_value)
+        .sum()
+}
@@ -0,0 +1,217 @@
+use crate::archive::{BatchOptions, BatchResponse};
+use crate::budget::{size_of_batch, size_of_value};
+use crate::entities::{Batch, Call, Extrinsic, Relations};
+use crate::fields::ExtrinsicFields;
+use crate::selection::CallDataSelection;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+fn pos(item: &Value) -> i64 {
+    item.get("pos").and_then(Value::as_i64).unwrap()
+}
+
+fn items(batch: &Batch) -> impl Iterator<Item = &Value> {
+    batch
+        .extrinsics
+        .iter()
+        .chain(&batch.calls)
+        .chain(&batch.events)
+}
+
+/// Keeps selected items whose positions satisfy `f` along with the items they refer to.
+pub(crate) fn retain(batch: &mut Batch, f: impl Fn(i64) -> bool) {
+    let relations = &batch.relations;
+    let selected = items(batch)
+        .map(pos)
+        .filter(|pos| !relations.is_related(*pos) && f(*pos));
+    let kept = relations.reach(selected);
+    batch.extrinsics.retain(|item| kept.contains(&pos(item)));
+    batch.calls.retain(|item| kept.contains(&pos(item)));
+    batch.events.retain(|item| kept.contains(&pos(item)));
+}
+
+// keeps items fitting into the limit, returns position of the first removed one
+fn split(batch: &mut Batch, limit: usize) -> Option<i64> {
+    let sizes: HashMap<i64, usize> = items(batch)
+        .map(|item| (pos(item), size_of_value(item)))
+        .collect();
+    let mut selected: Vec<i64> = sizes
+        .keys()
+        .copied()
+        .filter(|pos| !batch.relations.is_related(*pos))
+        .collect();
+    selected.sort_unstable();
+
+    // a selected item takes the related items which aren't counted yet
+    let mut counted = HashSet::new();
+    let mut size = 0;
+    let next_pos = selected.iter().enumerate().find_map(|(index, pos)| {
+        for reached in batch.relations.reach([*pos]) {
+            if counted.insert(reached) {
+                size += sizes.get(&reached).copied().unwrap_or_default();
+            }
+        }
+        // the first item is always kept so that a client makes progress
+        if size > limit && index != 0 {
+            Some(*pos)
+        } else {
+            None
+        }
+    })?;
+    retain(batch, |pos| pos < next_pos);
+    Some(next_pos)
+}
+
+/// Cuts a response resumed from `(from_block, from_pos)` to the byte limit.
+///
+/// Items of `from_block` placed before `from_pos` are dropped. Blocks are kept
+/// whole as long as they fit, only a block which alone exceeds the limit is split.
+/// Returns the block and position (if the block was split) to continue from.
+fn paginate(
+    data: &mut Vec<Batch>,
+    from_block: i32,
+    from_pos: i64,
+    limit: usize,
+) -> Option<(i32, Option<i64>)> {
+    if let Some(batch) = data.first_mut() {
+        if batch.header.height == i64::from(from_block) {
+            retain(batch, |pos| pos >= from_pos);
+        }
+    }
+
+    let mut size = 0;
+    for (index, batch) in data.iter_mut().enumerate() {
+        size += size_of_batch(std::slice::from_ref(batch));
+        if size <= limit {
+            continue;
+        }
+        let height = i32::try_from(batch.header.height).unwrap();
+        if index == 0 {
+            let next = match split(batch, limit) {
+                Some(next_pos) => (height, Some(next_pos)),
+                None => (height + 1, None),
+            };
+            data.truncate(1);
+            return Some(next);
+        }
+        data.truncate(index);
+        return Some((height, None));
+    }
+    None
+}
+
+/// Applies the cursor of a request, if any, to its response.
+pub fn apply(options: &BatchOptions, response: &mut BatchResponse, limit: usize) {
+    if let Some(from_pos) = options.from_pos {
+        let next = paginate(&mut response.data, options.from_block, from_pos, limit);
+        if let Some((next_block, next_pos)) = next {
+            // the loader might have stopped inside the only block already
+            let stopped_inside = next_pos.is_none()
+                && response.next_pos.is_some()
+                && response.next_block == Some(next_block - 1);
+            if !stopped_inside {
+                response.next_block = Some(next_block);
+                response.next_pos = next_pos;
+            }
+        }
+    }
+}
+
+enum Target<'a> {
+    Call(&'a str),
+    Extrinsic(&'a str),
+    Pos(i64),
+}
+
+/// Collects relations of returned items, an item refers to another one if
+/// fields of the latter are requested through it.
+#[derive(Default)]
+pub(crate) struct RelationsBuilder<'a> {
+    calls: HashMap<&'a str, i64>,
+    extrinsics: HashMap<&'a str, i64>,
+    // whether an item at the block and position is selected by itself
+    selected: HashMap<(&'a str, i64), bool>,
+    refs: Vec<(&'a str, Target<'a>, Target<'a>)>,
+}
+
+impl<'a> RelationsBuilder<'a> {
+    pub fn call(&mut self, call: &'a Call, fields: &CallDataSelection, selected: bool) {
+        self.calls.insert(&call.id, call.pos);
+        self.item(&call.block_id, call.pos, selected);
+        if let Some(parent_id) = &call.parent_id {
+            if fields.call.parent.any() {
+                let parent = Target::Call(parent_id);
+                self.refer(&call.block_id, Target::Pos(call.pos), parent);
+            }
+        }
+        if fields.extrinsic.any() {
+            let extrinsic = Target::Extrinsic(&call.extrinsic_id);
+            self.refer(&call.block_id, Target::Pos(call.pos), extrinsic);
+        }
+    }
+
+    pub fn extrinsic(&mut self, extrinsic: &'a Extrinsic, fields: &ExtrinsicFields) {
+        self.extrinsics.insert(&extrinsic.id, extrinsic.pos);
+        self.item(&extrinsic.block_id, extrinsic.pos, false);
+        if fields.call.any() {
+            let call = Target::Call(&extrinsic.call_id);
+            self.refer(&extrinsic.block_id, Target::Pos(extrinsic.pos), call);
+        }
+    }
+
+    /// Adds an event or an EVM log given ids of the items it requests fields of,
+    /// an event which isn't selected by itself is returned along with its call.
+    pub fn event(
+        &mut self,
+        block_id: &'a str,
+        pos: i64,
+        extrinsic_id: Option<&'a str>,
+        call_id: Option<&'a str>,
+        selected: bool,
+    ) {
+        if let Some(extrinsic_id) = extrinsic_id {
+            self.refer(block_id, Target::Pos(pos), Target::Extrinsic(extrinsic_id));
+        }
+        if let Some(call_id) = call_id {
+            self.refer(block_id, Target::Pos(pos), Target::Call(call_id));
+            if !selected {
+                self.refer(block_id, Target::Call(call_id), Target::Pos(pos));
+            }
+        }
+        self.item(block_id, pos, selected);
+    }
+
+    fn item(&mut self, block_id: &'a str, pos: i64, selected: bool) {
+        *self.selected.entry((block_id, pos)).or_default() |= selected;
+    }
+
+    fn refer(&mut self, block_id: &'a str, from: Target<'a>, to: Target<'a>) {
+        self.refs.push((block_id, from, to));
+    }
+
+    fn resolve(&self, target: &Target) -> Option<i64> {
+        match target {
+            Target::Call(id) => self.calls.get(id).copied(),
+            Target::Extrinsic(id) => self.extrinsics.get(id).copied(),
+            Target::Pos(pos) => Some(*pos),
+        }
+    }
+
+    /// Relations of the items by block id
+    pub fn build(self) -> HashMap<String, Relations> {
+        let mut relations: HashMap<String, Relations> = HashMap::new();
+        for ((block_id, pos), selected) in &self.selected {
+            if !selected {
+                let block = relations.entry(block_id.to_string()).or_default();
+                block.related(*pos);
+            }
+        }
+        for (block_id, from, to) in &self.refs {
+            if let (Some(from), Some(to)) = (self.resolve(from), self.resolve(to)) {
+                let block = relations.entry(block_id.to_string()).or_default();
+                block.refer(from, to);
+            }
+        }
+        relations
+    }
+}
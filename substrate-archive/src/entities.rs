@@ -1,9 +1,11 @@
 use async_graphql::SimpleObject;
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
+use serde::Deserialize;
 use sqlx::FromRow;
+use std::collections::{HashMap, HashSet};
 
-#[derive(FromRow, Debug, SimpleObject)]
+#[derive(FromRow, Debug, Clone, Deserialize, SimpleObject)]
 pub struct BlockHeader {
     pub id: String,
     pub height: i64,
@@ -16,7 +18,7 @@ pub struct BlockHeader {
     pub validator: Option<String>,
 }
 
-#[derive(FromRow, Debug)]
+#[derive(FromRow, Debug, Deserialize)]
 pub struct Event {
     pub id: String,
     pub block_id: String,
@@ -43,7 +45,7 @@ pub struct EvmLog {
     pub evm_tx_hash: String,
 }
 
-#[derive(FromRow, Debug)]
+#[derive(FromRow, Debug, Deserialize)]
 pub struct Call {
     pub id: String,
     pub parent_id: Option<String>,
@@ -57,7 +59,7 @@ pub struct Call {
     pub pos: i64,
 }
 
-#[derive(FromRow)]
+#[derive(FromRow, Deserialize)]
 pub struct Extrinsic {
     pub id: String,
     pub block_id: String,
@@ -79,9 +81,50 @@ pub struct Batch {
     pub extrinsics: Vec<serde_json::Value>,
     pub calls: Vec<serde_json::Value>,
     pub events: Vec<serde_json::Value>,
+    #[graphql(skip)]
+    pub relations: Relations,
+}
+
+/// Items of a block which are returned only because other items refer to them.
+///
+/// Items are identified by their positions. A cursor cuts the selected items of
+/// a block by position and keeps the related items the remaining ones refer to.
+#[derive(Debug, Default)]
+pub struct Relations {
+    related: HashSet<i64>,
+    refs: HashMap<i64, Vec<i64>>,
+}
+
+impl Relations {
+    /// Marks the item as not selected by itself
+    pub(crate) fn related(&mut self, pos: i64) {
+        self.related.insert(pos);
+    }
+
+    pub(crate) fn refer(&mut self, from: i64, to: i64) {
+        self.refs.entry(from).or_default().push(to);
+    }
+
+    pub(crate) fn is_related(&self, pos: i64) -> bool {
+        self.related.contains(&pos)
+    }
+
+    /// Positions of the given items and of all items they refer to
+    pub(crate) fn reach(&self, positions: impl IntoIterator<Item = i64>) -> HashSet<i64> {
+        let mut reached = HashSet::new();
+        let mut stack: Vec<i64> = positions.into_iter().collect();
+        while let Some(pos) = stack.pop() {
+            if reached.insert(pos) {
+                if let Some(refs) = self.refs.get(&pos) {
+                    stack.extend(refs);
+                }
+            }
+        }
+        reached
+    }
 }
 
-#[derive(FromRow, Debug, SimpleObject)]
+#[derive(FromRow, Debug, Clone, Deserialize, SimpleObject)]
 pub struct Metadata {
     pub id: String,
     pub spec_name: String,
@@ -91,7 +134,51 @@ pub struct Metadata {
     pub hex: String,
 }
 
-#[derive(FromRow, Debug, SimpleObject)]
+#[derive(Debug, SimpleObject)]
 pub struct Status {
     pub head: i64,
+    /// Highest block which can't be reorganized
+    pub finalized_head: i64,
+    pub head_hash: Option<String>,
+    pub head_timestamp: Option<DateTime<Utc>>,
+    /// Lowest ingested block, -1 if the archive is empty
+    pub lowest_height: i64,
+    /// Time (ms) passed since the timestamp of the head block
+    pub ingest_lag_ms: Option<i64>,
+    /// Spec version of the latest runtime
+    pub spec_version: Option<i64>,
+    /// Approximate number of rows in tables of items
+    pub tables: Vec<TableStats>,
+}
+
+impl Status {
+    /// Status of the archive with the given head, other statistics are left empty.
+    ///
+    /// Without `confirmation_depth` every stored block is considered finalized
+    /// as substrate-ingest writes finalized blocks only.
+    pub fn new(head: Option<&BlockHeader>, confirmation_depth: Option<u32>) -> Status {
+        let height = head.map_or(-1, |block| block.height);
+        let finalized_head = match confirmation_depth {
+            Some(depth) if height >= 0 => (height - i64::from(depth)).max(-1),
+            _ => height,
+        };
+        Status {
+            head: height,
+            finalized_head,
+            head_hash: head.map(|block| block.hash.clone()),
+            head_timestamp: head.map(|block| block.timestamp),
+            lowest_height: -1,
+            ingest_lag_ms: head
+                .map(|block| (Utc::now() - block.timestamp).num_milliseconds().max(0)),
+            spec_version: None,
+            tables: vec![],
+        }
+    }
+}
+
+#[derive(Debug, SimpleObject)]
+pub struct TableStats {
+    pub name: String,
+    /// Unknown until the database collects statistics of the table
+    pub rows: Option<i64>,
 }
@@ -1,6 +1,12 @@
 #[derive(Debug)]
 pub enum Error {
     Internal(String),
+    /// Block at `height` doesn't follow the parent hash expected by a client
+    Reorg {
+        height: i32,
+        expected: String,
+        actual: String,
+    },
 }
 
 impl std::convert::From<sqlx::Error> for Error {
@@ -9,10 +15,31 @@ impl std::convert::From<sqlx::Error> for Error {
     }
 }
 
+impl std::convert::From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Internal(err.to_string())
+    }
+}
+
+impl std::convert::From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Internal(err.to_string())
+    }
+}
+
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             Error::Internal(message) => write!(f, "{}", message),
+            Error::Reorg {
+                height,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "chain reorganization: parent of block {} is {}, not {}",
+                height, actual, expected
+            ),
         }
     }
 }
@@ -0,0 +1,91 @@
+use serde_json::{json, Value};
+
+/// JSON Schema of a `Batch` as it's returned by the `batch` query.
+///
+/// Only `id`, `pos` and a few other fields are always present in items,
+/// the rest depend on the requested fields of a selection.
+pub fn batch() -> Value {
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "Batch",
+        "type": "object",
+        "properties": {
+            "header": { "$ref": "#/$defs/BlockHeader" },
+            "extrinsics": { "type": "array", "items": { "$ref": "#/$defs/Extrinsic" } },
+            "calls": { "type": "array", "items": { "$ref": "#/$defs/Call" } },
+            "events": { "type": "array", "items": { "$ref": "#/$defs/Event" } }
+        },
+        "$defs": {
+            "BlockHeader": {
+                "type": "object",
+                "properties": {
+                    "id": { "type": "string" },
+                    "height": { "type": "integer" },
+                    "hash": { "type": "string" },
+                    "parentHash": { "type": "string" },
+                    "stateRoot": { "type": "string" },
+                    "extrinsicsRoot": { "type": "string" },
+                    "timestamp": { "type": "string", "format": "date-time" },
+                    "specId": { "type": "string" },
+                    "validator": { "type": ["string", "null"] }
+                },
+                "additionalProperties": false
+            },
+            "Extrinsic": {
+                "type": "object",
+                "properties": {
+                    "id": { "type": "string" },
+                    "pos": { "type": "integer" },
+                    "indexInBlock": { "type": "integer" },
+                    "version": { "type": "integer" },
+                    "signature": {},
+                    "callId": { "type": "string" },
+                    "fee": { "type": ["string", "null"] },
+                    "tip": { "type": ["string", "null"] },
+                    "success": { "type": "boolean" },
+                    "error": {},
+                    "hash": { "type": "string" }
+                },
+                "required": ["id", "pos"],
+                "additionalProperties": false
+            },
+            "Call": {
+                "type": "object",
+                "properties": {
+                    "id": { "type": "string" },
+                    "pos": { "type": "integer" },
+                    "name": { "type": "string" },
+                    "success": { "type": "boolean" },
+                    "error": {},
+                    "origin": {},
+                    "args": {},
+                    "parentId": { "type": ["string", "null"] },
+                    "extrinsicId": { "type": "string" }
+                },
+                "required": ["id", "pos", "name", "success"],
+                "additionalProperties": false
+            },
+            "Event": {
+                "type": "object",
+                "properties": {
+                    "id": { "type": "string" },
+                    "pos": { "type": "integer" },
+                    "name": { "type": "string" },
+                    "indexInBlock": { "type": "integer" },
+                    "phase": { "type": "string" },
+                    "extrinsicId": { "type": ["string", "null"] },
+                    "callId": { "type": ["string", "null"] },
+                    "args": {},
+                    "evmTxHash": {
+                        "description": "Present only for `EVM.Log` events requested via `evmLogs`",
+                        "type": "string"
+                    }
+                },
+                "required": ["id", "pos", "name"],
+                "additionalProperties": false
+            }
+        },
+        "required": ["header", "extrinsics", "calls", "events"],
+        "additionalProperties": false
+    })
+}
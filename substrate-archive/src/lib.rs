@@ -1,7 +1,11 @@
 pub mod archive;
+pub mod budget;
+mod cursor;
 pub mod entities;
 pub mod error;
 pub mod fields;
+pub mod json_schema;
+pub mod memory;
 mod metrics;
 pub mod postgres;
 pub mod selection;
@@ -0,0 +1,561 @@
+use super::Storage;
+use crate::archive::Selections;
+use crate::cursor::RelationsBuilder;
+use crate::entities::{Batch, BlockHeader, Call, Event, EvmLog};
+use crate::fields::{CallFields, EventFields, EvmLogFields, ExtrinsicFields};
+use crate::postgres::serializer::{
+    CallSerializer, EventSerializer, EvmLogSerializer, ExtrinsicSerializer,
+};
+use crate::postgres::utils::unify_and_merge;
+use crate::selection::{CallDataSelection, EventDataSelection};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+// names of the entities substrate-ingest puts into the pallet specific tables
+const EVM_LOG: &str = "EVM.Log";
+const ETHEREUM_TRANSACT: &str = "Ethereum.transact";
+const ETHEREUM_EXECUTED: &str = "Ethereum.Executed";
+const CONTRACTS_CONTRACT_EMITTED: &str = "Contracts.ContractEmitted";
+const GEAR_MESSAGE_ENQUEUED: &str = "Gear.MessageEnqueued";
+const GEAR_USER_MESSAGE_SENT: &str = "Gear.UserMessageSent";
+const ACALA_EVM_EXECUTED: &str = "EVM.Executed";
+const ACALA_EVM_EXECUTED_FAILED: &str = "EVM.ExecutedFailed";
+
+pub struct BatchLoader<'a> {
+    storage: &'a Storage,
+    selections: &'a Selections,
+}
+
+#[derive(Default)]
+struct SelectedFields {
+    extrinsic: HashMap<String, ExtrinsicFields>,
+    event: HashMap<String, EventFields>,
+    log: HashMap<String, EvmLogFields>,
+    call: HashMap<String, CallDataSelection>,
+    call_to_load: HashMap<String, CallFields>,
+    // items matching selections, the rest is returned because they refer to it
+    selected_calls: HashSet<String>,
+    selected_events: HashSet<String>,
+}
+
+impl SelectedFields {
+    fn add_extrinsic(&mut self, extrinsic_id: &str, fields: &ExtrinsicFields) {
+        self.extrinsic
+            .entry(extrinsic_id.to_string())
+            .and_modify(|value| value.merge(fields))
+            .or_insert_with(|| fields.clone());
+    }
+
+    fn add_call(&mut self, call_id: &str, data: &CallDataSelection) {
+        self.call
+            .entry(call_id.to_string())
+            .and_modify(|value| {
+                value.call.merge(&data.call);
+                value.extrinsic.merge(&data.extrinsic);
+            })
+            .or_insert_with(|| data.clone());
+    }
+
+    fn add_call_to_load(&mut self, call_id: &str, fields: &CallFields) {
+        if let Some(value) = self.call.get_mut(call_id) {
+            value.call.merge(fields);
+        } else {
+            self.call_to_load
+                .entry(call_id.to_string())
+                .and_modify(|value| value.merge(fields))
+                .or_insert_with(|| fields.clone());
+        }
+    }
+}
+
+impl<'a> BatchLoader<'a> {
+    pub fn new(storage: &'a Storage, selections: &'a Selections) -> BatchLoader<'a> {
+        BatchLoader {
+            storage,
+            selections,
+        }
+    }
+
+    pub fn load(&self, from_block: i32, to_block: i32, include_all_blocks: bool) -> Vec<Batch> {
+        let from_block = i64::from(from_block);
+        let to_block = i64::from(to_block);
+        let range: Vec<&BlockHeader> = self
+            .storage
+            .blocks
+            .iter()
+            .filter(|block| block.height >= from_block && block.height <= to_block)
+            .collect();
+        let block_ids: HashSet<&str> = range.iter().map(|block| block.id.as_str()).collect();
+
+        let selections = self.selections;
+        let calls = self.find_calls(&block_ids, |call| {
+            selections
+                .call
+                .iter()
+                .any(|selection| selection.r#match(call))
+        });
+        let eth_transactions = self.find_calls(&block_ids, |call| {
+            call.name == ETHEREUM_TRANSACT
+                && selections
+                    .eth_transact
+                    .iter()
+                    .any(|selection| selection.r#match(call))
+        });
+        let eth_transaction_ids: HashSet<&str> = eth_transactions
+            .iter()
+            .map(|call| call.id.as_str())
+            .collect();
+        let eth_executed = self.find_events(&block_ids, |event| {
+            event.name == ETHEREUM_EXECUTED
+                && event
+                    .call_id
+                    .as_ref()
+                    .is_some_and(|call_id| eth_transaction_ids.contains(call_id.as_str()))
+        });
+        let events = self.find_events(&block_ids, |event| {
+            selections
+                .event
+                .iter()
+                .any(|selection| selection.r#match(event))
+        });
+        let contracts_events = self.find_events(&block_ids, |event| {
+            event.name == CONTRACTS_CONTRACT_EMITTED
+                && selections
+                    .contracts_event
+                    .iter()
+                    .any(|selection| selection.r#match(event))
+        });
+        let messages_enqueued = self.find_events(&block_ids, |event| {
+            event.name == GEAR_MESSAGE_ENQUEUED
+                && selections
+                    .gear_message_enqueued
+                    .iter()
+                    .any(|selection| selection.r#match(event))
+        });
+        let messages_sent = self.find_events(&block_ids, |event| {
+            event.name == GEAR_USER_MESSAGE_SENT
+                && selections
+                    .gear_user_message_sent
+                    .iter()
+                    .any(|selection| selection.r#match(event))
+        });
+        let acala_evm_executed = self.find_events(&block_ids, |event| {
+            event.name == ACALA_EVM_EXECUTED
+                && selections
+                    .acala_evm_executed
+                    .iter()
+                    .any(|selection| selection.r#match(event))
+        });
+        let acala_evm_failed = self.find_events(&block_ids, |event| {
+            event.name == ACALA_EVM_EXECUTED_FAILED
+                && selections
+                    .acala_evm_executed_failed
+                    .iter()
+                    .any(|selection| selection.r#match(event))
+        });
+        let evm_logs = self.find_evm_logs(&block_ids);
+
+        let mut fields = SelectedFields::default();
+        for call in &eth_transactions {
+            for selection in &selections.eth_transact {
+                if selection.r#match(call) {
+                    self.process_call(call, &selection.data, &mut fields);
+                }
+            }
+        }
+        for call in &calls {
+            for selection in &selections.call {
+                if selection.r#match(call) {
+                    self.process_call(call, &selection.data, &mut fields);
+                }
+            }
+        }
+        for event in &events {
+            for selection in &selections.event {
+                if selection.r#match(event) {
+                    self.process_event(event, &selection.data, &mut fields);
+                }
+            }
+        }
+        for event in &messages_enqueued {
+            for selection in &selections.gear_message_enqueued {
+                if selection.r#match(event) {
+                    self.process_event(event, &selection.data, &mut fields);
+                }
+            }
+        }
+        for event in &messages_sent {
+            for selection in &selections.gear_user_message_sent {
+                if selection.r#match(event) {
+                    self.process_event(event, &selection.data, &mut fields);
+                }
+            }
+        }
+        for event in &acala_evm_executed {
+            for selection in &selections.acala_evm_executed {
+                if selection.r#match(event) {
+                    self.process_event(event, &selection.data, &mut fields);
+                }
+            }
+        }
+        for event in &acala_evm_failed {
+            for selection in &selections.acala_evm_executed_failed {
+                if selection.r#match(event) {
+                    self.process_event(event, &selection.data, &mut fields);
+                }
+            }
+        }
+        for event in &contracts_events {
+            for selection in &selections.contracts_event {
+                if selection.r#match(event) {
+                    self.process_event(event, &selection.data, &mut fields);
+                }
+            }
+        }
+        for event in &eth_executed {
+            let event_fields = EventFields::new(true);
+            fields
+                .event
+                .entry(event.id.clone())
+                .and_modify(|value| value.merge(&event_fields))
+                .or_insert(event_fields);
+        }
+        for log in &evm_logs {
+            for selection in &selections.evm_log {
+                if selection.r#match(log) {
+                    fields
+                        .log
+                        .entry(log.id.clone())
+                        .and_modify(|value| value.merge(&selection.data.event))
+                        .or_insert_with(|| selection.data.event.clone());
+                    if let Some(extrinsic_id) = &log.extrinsic_id {
+                        if selection.data.event.extrinsic.any() {
+                            fields.add_extrinsic(extrinsic_id, &selection.data.event.extrinsic);
+                        }
+                    }
+                    if let Some(call_id) = &log.call_id {
+                        fields.add_call_to_load(call_id, &selection.data.event.call);
+                    }
+                }
+            }
+        }
+
+        let mut extrinsic_calls = vec![];
+        for (extrinsic_id, extrinsic_fields) in &fields.extrinsic {
+            if let Some(extrinsic) = self.storage.extrinsics.get(extrinsic_id) {
+                if extrinsic_fields.call.any() {
+                    extrinsic_calls
+                        .push((extrinsic.call_id.clone(), extrinsic_fields.call.clone()));
+                }
+            }
+        }
+        for (call_id, call_fields) in extrinsic_calls {
+            fields.add_call_to_load(&call_id, &call_fields);
+        }
+
+        let mut calls_to_load: Vec<(String, CallFields)> = fields.call_to_load.drain().collect();
+        calls_to_load.sort_by(|a, b| a.0.cmp(&b.0));
+        for (call_id, call_fields) in calls_to_load {
+            if let Some(call) = self.storage.calls.get(&call_id) {
+                let data = CallDataSelection {
+                    call: call_fields,
+                    extrinsic: ExtrinsicFields::new(false),
+                };
+                self.visit_parent_call(call, &data, &mut fields);
+                fields.add_call(&call.id, &data);
+            }
+        }
+
+        let blocks: Vec<&BlockHeader> = if include_all_blocks {
+            range
+        } else {
+            let mut ids: HashSet<&str> = HashSet::new();
+            calls
+                .iter()
+                .chain(eth_transactions.iter())
+                .for_each(|call| {
+                    ids.insert(&call.block_id);
+                });
+            events
+                .iter()
+                .chain(contracts_events.iter())
+                .chain(messages_enqueued.iter())
+                .chain(messages_sent.iter())
+                .chain(acala_evm_executed.iter())
+                .chain(acala_evm_failed.iter())
+                .for_each(|event| {
+                    ids.insert(&event.block_id);
+                });
+            evm_logs.iter().for_each(|log| {
+                ids.insert(&log.block_id);
+            });
+            range
+                .into_iter()
+                .filter(|block| ids.contains(block.id.as_str()))
+                .collect()
+        };
+
+        self.create_batch(blocks, &evm_logs, &fields)
+    }
+
+    fn find_calls(
+        &self,
+        block_ids: &HashSet<&str>,
+        predicate: impl Fn(&Call) -> bool,
+    ) -> Vec<&'a Call> {
+        let mut calls: Vec<&Call> = self
+            .storage
+            .calls
+            .values()
+            .filter(|call| block_ids.contains(call.block_id.as_str()) && predicate(call))
+            .collect();
+        calls.sort_by(|a, b| a.id.cmp(&b.id));
+        calls
+    }
+
+    fn find_events(
+        &self,
+        block_ids: &HashSet<&str>,
+        predicate: impl Fn(&Event) -> bool,
+    ) -> Vec<&'a Event> {
+        let mut events: Vec<&Event> = self
+            .storage
+            .events
+            .values()
+            .filter(|event| block_ids.contains(event.block_id.as_str()) && predicate(event))
+            .collect();
+        events.sort_by(|a, b| a.id.cmp(&b.id));
+        events
+    }
+
+    fn find_evm_logs(&self, block_ids: &HashSet<&str>) -> Vec<EvmLog> {
+        if self.selections.evm_log.is_empty() {
+            return vec![];
+        }
+        let mut hash_by_extrinsic: HashMap<&str, &str> = HashMap::new();
+        for event in self.find_events(block_ids, |event| event.name == ETHEREUM_EXECUTED) {
+            if let (Some(extrinsic_id), Some(args)) = (&event.extrinsic_id, &event.args) {
+                let hash = args
+                    .get(2)
+                    .or_else(|| args.get("transactionHash"))
+                    .and_then(Value::as_str);
+                if let Some(hash) = hash {
+                    hash_by_extrinsic.insert(extrinsic_id, hash);
+                }
+            }
+        }
+        self.find_events(block_ids, |event| event.name == EVM_LOG)
+            .into_iter()
+            .map(|event| {
+                let evm_tx_hash = event
+                    .extrinsic_id
+                    .as_ref()
+                    .and_then(|extrinsic_id| hash_by_extrinsic.get(extrinsic_id.as_str()))
+                    .map_or_else(String::new, |hash| hash.to_string());
+                EvmLog {
+                    id: event.id.clone(),
+                    block_id: event.block_id.clone(),
+                    index_in_block: event.index_in_block,
+                    phase: event.phase.clone(),
+                    extrinsic_id: event.extrinsic_id.clone(),
+                    call_id: event.call_id.clone(),
+                    name: event.name.clone(),
+                    args: event.args.clone(),
+                    pos: event.pos,
+                    evm_tx_hash,
+                }
+            })
+            .filter(|log| {
+                self.selections
+                    .evm_log
+                    .iter()
+                    .any(|selection| selection.r#match(log))
+            })
+            .collect()
+    }
+
+    fn process_call(&self, call: &Call, data: &CallDataSelection, fields: &mut SelectedFields) {
+        fields.selected_calls.insert(call.id.clone());
+        fields.add_call(&call.id, data);
+        if data.extrinsic.any() {
+            fields.add_extrinsic(&call.extrinsic_id, &data.extrinsic);
+        }
+        self.visit_parent_call(call, data, fields);
+    }
+
+    fn process_event(&self, event: &Event, data: &EventDataSelection, fields: &mut SelectedFields) {
+        fields.selected_events.insert(event.id.clone());
+        fields
+            .event
+            .entry(event.id.clone())
+            .and_modify(|value| value.merge(&data.event))
+            .or_insert_with(|| data.event.clone());
+
+        if let Some(extrinsic_id) = &event.extrinsic_id {
+            if data.event.extrinsic.any() {
+                fields.add_extrinsic(extrinsic_id, &data.event.extrinsic);
+            }
+        }
+
+        if let Some(call_id) = &event.call_id {
+            if data.event.call.any() {
+                fields.add_call_to_load(call_id, &data.event.call);
+            }
+        }
+    }
+
+    fn visit_parent_call(
+        &self,
+        call: &Call,
+        data: &CallDataSelection,
+        fields: &mut SelectedFields,
+    ) {
+        if let Some(parent_id) = &call.parent_id {
+            if data.call.parent.any() {
+                // fixtures given to `MemoryArchive::new` aren't validated
+                let parent = match self.storage.calls.get(parent_id) {
+                    Some(parent) => parent,
+                    None => return,
+                };
+                let parent_fields = CallDataSelection {
+                    call: CallFields::from_parent(&data.call.parent),
+                    extrinsic: ExtrinsicFields::new(false),
+                };
+                self.visit_parent_call(parent, &parent_fields, fields);
+                fields.add_call(&parent.id, &parent_fields);
+            }
+        }
+    }
+
+    fn create_batch(
+        &self,
+        blocks: Vec<&BlockHeader>,
+        evm_logs: &[EvmLog],
+        fields: &SelectedFields,
+    ) -> Vec<Batch> {
+        let mut events_by_block: HashMap<&str, Vec<Value>> = HashMap::new();
+        for (event_id, event_fields) in &fields.event {
+            let event = &self.storage.events[event_id];
+            let serializer = EventSerializer {
+                event,
+                fields: event_fields,
+            };
+            let data = serde_json::to_value(serializer).unwrap();
+            events_by_block
+                .entry(&event.block_id)
+                .or_default()
+                .push(data);
+        }
+        for log in evm_logs {
+            if let Some(log_fields) = fields.log.get(&log.id) {
+                let serializer = EvmLogSerializer {
+                    log,
+                    fields: log_fields,
+                };
+                let data = serde_json::to_value(serializer).unwrap();
+                events_by_block.entry(&log.block_id).or_default().push(data);
+            }
+        }
+
+        let mut calls_by_block: HashMap<&str, Vec<Value>> = HashMap::new();
+        for (call_id, call_fields) in &fields.call {
+            let call = &self.storage.calls[call_id];
+            let serializer = CallSerializer {
+                call,
+                fields: call_fields,
+            };
+            let data = serde_json::to_value(serializer).unwrap();
+            calls_by_block.entry(&call.block_id).or_default().push(data);
+        }
+
+        let mut relations = RelationsBuilder::default();
+        for (call_id, call_fields) in &fields.call {
+            let call = &self.storage.calls[call_id];
+            let selected = fields.selected_calls.contains(call_id);
+            relations.call(call, call_fields, selected);
+        }
+        for (event_id, event_fields) in &fields.event {
+            let event = &self.storage.events[event_id];
+            relations.event(
+                &event.block_id,
+                event.pos,
+                event
+                    .extrinsic_id
+                    .as_deref()
+                    .filter(|_| event_fields.extrinsic.any()),
+                event.call_id.as_deref().filter(|_| event_fields.call.any()),
+                fields.selected_events.contains(event_id),
+            );
+        }
+        for log in evm_logs {
+            if let Some(log_fields) = fields.log.get(&log.id) {
+                relations.event(
+                    &log.block_id,
+                    log.pos,
+                    log.extrinsic_id
+                        .as_deref()
+                        .filter(|_| log_fields.extrinsic.any()),
+                    log.call_id.as_deref().filter(|_| log_fields.call.any()),
+                    true,
+                );
+            }
+        }
+
+        let mut extrinsics_by_block: HashMap<&str, Vec<Value>> = HashMap::new();
+        for (extrinsic_id, extrinsic_fields) in &fields.extrinsic {
+            if let Some(extrinsic) = self.storage.extrinsics.get(extrinsic_id) {
+                relations.extrinsic(extrinsic, extrinsic_fields);
+                let serializer = ExtrinsicSerializer {
+                    extrinsic,
+                    fields: extrinsic_fields,
+                };
+                let data = serde_json::to_value(serializer).unwrap();
+                extrinsics_by_block
+                    .entry(&extrinsic.block_id)
+                    .or_default()
+                    .push(data);
+            }
+        }
+        let mut relations = relations.build();
+
+        blocks
+            .into_iter()
+            .map(|block| {
+                let events = events_by_block
+                    .remove(block.id.as_str())
+                    .unwrap_or_default();
+                let event_fields = vec![
+                    "id",
+                    "blockId",
+                    "indexInBlock",
+                    "phase",
+                    "evmTxHash",
+                    "extrinsicId",
+                    "callId",
+                    "name",
+                    "args",
+                    "pos",
+                ];
+                let mut events = unify_and_merge(events, event_fields);
+                let mut calls = calls_by_block.remove(block.id.as_str()).unwrap_or_default();
+                let mut extrinsics = extrinsics_by_block
+                    .remove(block.id.as_str())
+                    .unwrap_or_default();
+                sort_by_pos(&mut events);
+                sort_by_pos(&mut calls);
+                sort_by_pos(&mut extrinsics);
+                Batch {
+                    extrinsics,
+                    calls,
+                    events,
+                    relations: relations.remove(&block.id).unwrap_or_default(),
+                    header: block.clone(),
+                }
+            })
+            .collect()
+    }
+}
+
+fn sort_by_pos(values: &mut [Value]) {
+    values.sort_by_key(|value| value.get("pos").and_then(Value::as_i64));
+}
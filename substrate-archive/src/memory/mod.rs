@@ -0,0 +1,236 @@
+use self::loader::BatchLoader;
+use crate::archive::{ArchiveService, BatchOptions, BatchResponse};
+use crate::budget::ResponseSizeLimit;
+use crate::cursor;
+use crate::entities::{BlockHeader, Call, Event, Extrinsic, Metadata, Status, TableStats};
+use crate::error::Error;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+
+mod loader;
+
+/// Archive content in the same shape as the substrate-ingest tables.
+#[derive(Deserialize, Default)]
+pub struct Fixtures {
+    #[serde(default)]
+    pub blocks: Vec<BlockHeader>,
+    #[serde(default)]
+    pub extrinsics: Vec<Extrinsic>,
+    #[serde(default)]
+    pub calls: Vec<Call>,
+    #[serde(default)]
+    pub events: Vec<Event>,
+    #[serde(default)]
+    pub metadata: Vec<Metadata>,
+}
+
+impl Fixtures {
+    /// Checks that calls refer to parent calls present in the fixtures.
+    pub fn validate(&self) -> Result<(), Error> {
+        let ids: HashSet<&str> = self.calls.iter().map(|call| call.id.as_str()).collect();
+        for call in &self.calls {
+            if let Some(parent_id) = &call.parent_id {
+                if !ids.contains(parent_id.as_str()) {
+                    return Err(Error::Internal(format!(
+                        "parent call {} of call {} is missing",
+                        parent_id, call.id
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Storage {
+    blocks: Vec<BlockHeader>,
+    extrinsics: HashMap<String, Extrinsic>,
+    calls: HashMap<String, Call>,
+    events: HashMap<String, Event>,
+    metadata: Vec<Metadata>,
+}
+
+impl Storage {
+    fn new(fixtures: Fixtures) -> Storage {
+        let mut blocks = fixtures.blocks;
+        blocks.sort_by_key(|block| block.height);
+        let mut metadata = fixtures.metadata;
+        metadata.sort_by_key(|metadata| metadata.block_height);
+        Storage {
+            blocks,
+            extrinsics: fixtures
+                .extrinsics
+                .into_iter()
+                .map(|extrinsic| (extrinsic.id.clone(), extrinsic))
+                .collect(),
+            calls: fixtures
+                .calls
+                .into_iter()
+                .map(|call| (call.id.clone(), call))
+                .collect(),
+            events: fixtures
+                .events
+                .into_iter()
+                .map(|event| (event.id.clone(), event))
+                .collect(),
+            metadata,
+        }
+    }
+
+    fn head(&self) -> Option<i64> {
+        self.blocks.last().map(|block| block.height)
+    }
+}
+
+/// `ArchiveService` over data kept in memory.
+///
+/// Intended for tests and local development, it doesn't need a database
+/// but follows the same selection rules as `PostgresArchive`.
+#[derive(Clone)]
+pub struct MemoryArchive {
+    storage: Arc<Storage>,
+    response_size_limit: ResponseSizeLimit,
+    confirmation_depth: Option<u32>,
+}
+
+#[async_trait::async_trait]
+impl ArchiveService for MemoryArchive {
+    async fn batch(&self, options: &BatchOptions) -> Result<BatchResponse, Error> {
+        if options.parent_hash.is_some() && options.from_block > 0 {
+            let from_block = i64::from(options.from_block);
+            let parents: Vec<String> = self
+                .storage
+                .blocks
+                .iter()
+                .filter_map(|block| {
+                    if block.height == from_block {
+                        Some(block.parent_hash.clone())
+                    } else if block.height == from_block - 1 {
+                        Some(block.hash.clone())
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+            options.check_parent_hash(&parents)?;
+        }
+        let to_block = match options.to_block {
+            Some(to_block) => to_block,
+            None => match self.storage.head() {
+                Some(head) => head.try_into().unwrap(),
+                None => {
+                    // archive is empty
+                    let next_block = match options.limit {
+                        Some(..) => None,
+                        None => Some(options.from_block),
+                    };
+                    return Ok(BatchResponse {
+                        data: vec![],
+                        next_block,
+                        next_pos: None,
+                    });
+                }
+            },
+        };
+
+        let loader = BatchLoader::new(&self.storage, &options.selections);
+        let to_block = options.scan_end(to_block);
+        let mut data = loader.load(options.from_block, to_block, options.include_all_blocks);
+        let mut response = match options.limit {
+            Some(limit) => {
+                data.truncate(limit.max(0) as usize);
+                BatchResponse {
+                    data,
+                    next_block: None,
+                    next_pos: None,
+                }
+            }
+            None => BatchResponse {
+                data,
+                next_block: Some(to_block + 1),
+                next_pos: None,
+            },
+        };
+        let response_size_limit = self.response_size_limit.resolve(options.max_bytes);
+        cursor::apply(options, &mut response, response_size_limit);
+        Ok(response)
+    }
+
+    async fn metadata(&self) -> Result<Vec<Metadata>, Error> {
+        Ok(self.storage.metadata.clone())
+    }
+
+    async fn metadata_by_id(&self, id: String) -> Result<Option<Metadata>, Error> {
+        let metadata = self
+            .storage
+            .metadata
+            .iter()
+            .find(|metadata| metadata.id == id)
+            .cloned();
+        Ok(metadata)
+    }
+
+    async fn status(&self) -> Result<Status, Error> {
+        let storage = &self.storage;
+        let tables = [
+            ("call", storage.calls.len()),
+            ("event", storage.events.len()),
+            ("extrinsic", storage.extrinsics.len()),
+        ];
+        Ok(Status {
+            lowest_height: storage.blocks.first().map_or(-1, |block| block.height),
+            spec_version: storage
+                .metadata
+                .last()
+                .map(|metadata| metadata.spec_version),
+            tables: tables
+                .into_iter()
+                .map(|(name, rows)| TableStats {
+                    name: name.to_string(),
+                    rows: Some(rows as i64),
+                })
+                .collect(),
+            ..Status::new(storage.blocks.last(), self.confirmation_depth)
+        })
+    }
+}
+
+impl Default for MemoryArchive {
+    fn default() -> MemoryArchive {
+        MemoryArchive::new(Fixtures::default())
+    }
+}
+
+impl MemoryArchive {
+    pub fn new(fixtures: Fixtures) -> MemoryArchive {
+        MemoryArchive {
+            storage: Arc::new(Storage::new(fixtures)),
+            response_size_limit: ResponseSizeLimit::default(),
+            confirmation_depth: None,
+        }
+    }
+
+    pub fn response_size_limit(mut self, value: ResponseSizeLimit) -> MemoryArchive {
+        self.response_size_limit = value;
+        self
+    }
+
+    /// Number of blocks `finalized_head` of the status lags behind the head
+    pub fn confirmation_depth(mut self, value: Option<u32>) -> MemoryArchive {
+        self.confirmation_depth = value;
+        self
+    }
+
+    pub fn from_json(json: &str) -> Result<MemoryArchive, Error> {
+        let fixtures = serde_json::from_str::<Fixtures>(json)?;
+        fixtures.validate()?;
+        Ok(MemoryArchive::new(fixtures))
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<MemoryArchive, Error> {
+        let json = std::fs::read_to_string(path)?;
+        MemoryArchive::from_json(&json)
+    }
+}
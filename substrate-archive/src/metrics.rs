@@ -1,11 +1,19 @@
 use lazy_static::lazy_static;
 use pin_project::pin_project;
-use prometheus::{register_histogram_vec, HistogramTimer, HistogramVec};
+use prometheus::{
+    opts, register_histogram, register_histogram_vec, register_int_counter,
+    register_int_counter_vec, register_int_gauge, Histogram, HistogramTimer, HistogramVec,
+    IntCounter, IntCounterVec, IntGauge,
+};
 use std::future::Future;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 
 const DB_TIME_SPENT_BUCKETS: &[f64; 8] = &[0.1, 0.5, 1.0, 3.0, 5.0, 10.0, 15.0, 30.0];
+const SCAN_RANGE_WIDTH_BUCKETS: &[f64; 8] = &[
+    10.0, 100.0, 500.0, 1_000.0, 5_000.0, 10_000.0, 50_000.0, 100_000.0,
+];
+const SCAN_ITERATIONS_BUCKETS: &[f64; 7] = &[1.0, 2.0, 3.0, 5.0, 10.0, 20.0, 50.0];
 
 lazy_static! {
     pub static ref DB_TIME_SPENT_SECONDS: HistogramVec = register_histogram_vec!(
@@ -15,6 +23,39 @@ lazy_static! {
         DB_TIME_SPENT_BUCKETS.to_vec()
     )
     .expect("Can't create a metric");
+    pub static ref SCAN_RANGE_MODEL_LOOKUPS: IntCounterVec = register_int_counter_vec!(
+        opts!(
+            "scan_range_model_lookups_total",
+            "scan range model lookups by result"
+        ),
+        &["result"]
+    )
+    .expect("Can't create a metric");
+    pub static ref SCAN_RANGE_MODEL_ENTRIES: IntGauge =
+        register_int_gauge!("scan_range_model_entries", "scan range model entries")
+            .expect("Can't create a metric");
+    pub static ref SCAN_START_RANGE_WIDTH: Histogram = register_histogram!(
+        "scan_start_range_width",
+        "range width a partial scan starts with",
+        SCAN_RANGE_WIDTH_BUCKETS.to_vec()
+    )
+    .expect("Can't create a metric");
+    pub static ref SCAN_ITERATIONS: Histogram = register_histogram!(
+        "scan_iterations",
+        "database round trips of a partial scan",
+        SCAN_ITERATIONS_BUCKETS.to_vec()
+    )
+    .expect("Can't create a metric");
+    pub static ref BATCH_CANCELLATIONS: IntCounter = register_int_counter!(
+        "batch_cancellations_total",
+        "batch requests dropped before completion"
+    )
+    .expect("Can't create a metric");
+    pub static ref CANCELLED_STATEMENTS: IntCounter = register_int_counter!(
+        "db_cancelled_statements_total",
+        "running statements aborted with pg_cancel_backend"
+    )
+    .expect("Can't create a metric");
 }
 
 #[pin_project]
@@ -1,8 +1,15 @@
+use super::hints::{IndexHints, QueryShape};
 use super::serializer::{CallSerializer, EventSerializer, EvmLogSerializer, ExtrinsicSerializer};
-use super::utils::unify_and_merge;
+use super::session::Session;
+use super::utils::{
+    blocks_by_ids, calls_by_ids, calls_with_parents_by_ids, events_by_ids, evm_logs_by_ids,
+    evm_tx_hashes_by_extrinsic_ids, executed_events_by_call_ids, extrinsics_by_ids, paginate,
+    unify_and_merge, BLOCK_COLUMNS, CALL_COLUMNS, EVENT_COLUMNS,
+};
 use super::DatabaseType;
 use crate::archive::Selections;
-use crate::entities::{Batch, BlockHeader, Call, Event, EvmLog, Extrinsic};
+use crate::cursor::{self, RelationsBuilder};
+use crate::entities::{Batch, BlockHeader, Call, Event, EvmLog, Extrinsic, Relations};
 use crate::error::Error;
 use crate::fields::{CallFields, EventFields, EvmLogFields, ExtrinsicFields};
 use crate::metrics::ObserverExt;
@@ -11,43 +18,81 @@ use crate::selection::{
     EthTransactSelection, EventDataSelection, EventSelection, EvmLogSelection,
     GearMessageEnqueuedSelection, GearUserMessageSentSelection,
 };
-use crate::sql::{select, Parameters};
-use sqlx::postgres::Postgres;
-use sqlx::Pool;
-use std::collections::HashMap;
+use crate::sql::{cast, select, Parameters};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+
+/// Items of a single block placed at `from_pos` or later,
+/// each item query loads at most `rows` of them.
+pub struct Page {
+    pub from_pos: i64,
+    pub rows: i64,
+    // last position up to which all the queried items are loaded
+    end: AtomicI64,
+}
+
+impl Page {
+    pub fn new(from_pos: i64, rows: i64) -> Page {
+        Page {
+            from_pos,
+            rows: rows.max(1),
+            end: AtomicI64::new(i64::MAX),
+        }
+    }
+
+    fn observe(&self, positions: impl ExactSizeIterator<Item = i64>) {
+        if i64::try_from(positions.len()).unwrap() >= self.rows {
+            if let Some(last) = positions.max() {
+                self.end.fetch_min(last, Ordering::SeqCst);
+            }
+        }
+    }
+
+    /// Position to continue from if a query was cut by the row limit.
+    fn next_pos(&self) -> Option<i64> {
+        match self.end.load(Ordering::SeqCst) {
+            i64::MAX => None,
+            end => Some(end + 1),
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct BatchLoader {
-    pool: Pool<Postgres>,
+    session: Session,
     database_type: DatabaseType,
+    index_hints: IndexHints,
+    page: Option<Arc<Page>>,
 }
 
 pub struct BatchResponse {
     pub data: Vec<Batch>,
     pub last_block: i32,
+    /// Position to continue `last_block` from if it wasn't loaded completely
+    pub next_pos: Option<i64>,
 }
 
-const EVENTS_BY_ID_QUERY: &str = "SELECT
-        id,
-        block_id,
-        index_in_block::int8,
-        phase,
-        extrinsic_id,
-        call_id,
-        name,
-        args,
-        pos::int8
-    FROM event
-    WHERE id = ANY($1::char(23)[])";
-
 impl BatchLoader {
-    pub fn new(pool: Pool<Postgres>, database_type: DatabaseType) -> BatchLoader {
+    pub fn new(
+        session: Session,
+        database_type: DatabaseType,
+        index_hints: IndexHints,
+    ) -> BatchLoader {
         BatchLoader {
-            pool,
+            session,
             database_type,
+            index_hints,
+            page: None,
         }
     }
 
+    /// Restricts the loaded items to the page, a single block is expected to be loaded then.
+    pub fn page(mut self, page: Page) -> BatchLoader {
+        self.page = Some(Arc::new(page));
+        self
+    }
+
     pub async fn load(
         &self,
         from_block: i32,
@@ -137,6 +182,7 @@ impl BatchLoader {
         let mut call_fields: HashMap<String, CallDataSelection> = HashMap::new();
 
         let mut call_fields_to_load: HashMap<String, CallFields> = HashMap::new();
+        let mut selected_calls: HashSet<String> = HashSet::new();
 
         let mut call_lookup: HashMap<String, &Call> = HashMap::new();
         for call in &eth_transactions {
@@ -149,6 +195,7 @@ impl BatchLoader {
         for call in &eth_transactions {
             for selection in &selections.eth_transact {
                 if selection.r#match(call) {
+                    selected_calls.insert(call.id.clone());
                     if let Some(fields) = call_fields.get_mut(&call.id) {
                         fields.call.merge(&selection.data.call);
                         fields.extrinsic.merge(&selection.data.extrinsic);
@@ -165,13 +212,14 @@ impl BatchLoader {
                             );
                         }
                     }
-                    self.visit_parent_call(&call, &selection.data, &call_lookup, &mut call_fields);
+                    self.visit_parent_call(call, &selection.data, &call_lookup, &mut call_fields);
                 }
             }
         }
         for call in &calls {
             for selection in &selections.call {
                 if selection.r#match(call) {
+                    selected_calls.insert(call.id.clone());
                     if let Some(fields) = call_fields.get_mut(&call.id) {
                         fields.call.merge(&selection.data.call);
                         fields.extrinsic.merge(&selection.data.extrinsic)
@@ -188,7 +236,7 @@ impl BatchLoader {
                             );
                         }
                     }
-                    self.visit_parent_call(&call, &selection.data, &call_lookup, &mut call_fields);
+                    self.visit_parent_call(call, &selection.data, &call_lookup, &mut call_fields);
                 }
             }
         }
@@ -269,6 +317,9 @@ impl BatchLoader {
             }
         }
         events.append(&mut contracts_events);
+        // executed events are returned along with their transactions
+        let selected_events: HashSet<String> =
+            events.iter().map(|event| event.id.clone()).collect();
         for event in &eth_executed {
             let f = EventFields::new(true);
             event_fields
@@ -310,7 +361,7 @@ impl BatchLoader {
             }
         }
 
-        let extrinsic_ids = extrinsic_fields.keys().cloned().collect();
+        let extrinsic_ids: Vec<String> = extrinsic_fields.keys().cloned().collect();
         let extrinsics = self.load_extrinsics(&extrinsic_ids).await?;
 
         for extrinsic in &extrinsics {
@@ -348,6 +399,42 @@ impl BatchLoader {
             calls.append(&mut additional_calls);
         }
 
+        let mut relations = RelationsBuilder::default();
+        for call in calls.iter().chain(&eth_transactions) {
+            if let Some(fields) = call_fields.get(&call.id) {
+                relations.call(call, fields, selected_calls.contains(&call.id));
+            }
+        }
+        for extrinsic in &extrinsics {
+            relations.extrinsic(extrinsic, &extrinsic_fields[&extrinsic.id]);
+        }
+        for event in &events {
+            let fields = &event_fields[&event.id];
+            relations.event(
+                &event.block_id,
+                event.pos,
+                event
+                    .extrinsic_id
+                    .as_deref()
+                    .filter(|_| fields.extrinsic.any()),
+                event.call_id.as_deref().filter(|_| fields.call.any()),
+                selected_events.contains(&event.id),
+            );
+        }
+        for log in &evm_logs {
+            let fields = &log_fields[&log.id];
+            relations.event(
+                &log.block_id,
+                log.pos,
+                log.extrinsic_id
+                    .as_deref()
+                    .filter(|_| fields.extrinsic.any()),
+                log.call_id.as_deref().filter(|_| fields.call.any()),
+                true,
+            );
+        }
+        let relations = relations.build();
+
         let mut events_by_block: HashMap<String, Vec<serde_json::Value>> = HashMap::new();
         for event in events {
             let fields = event_fields.get(&event.id).unwrap();
@@ -408,16 +495,25 @@ impl BatchLoader {
                 extrinsics_by_block.insert(extrinsic.block_id.clone(), vec![data]);
             }
         }
-        let batch = self.create_batch(
+        let mut batch = self.create_batch(
             blocks,
             events_by_block,
             calls_by_block,
             extrinsics_by_block,
             logs_by_block,
+            relations,
         );
+        // items past the row limit of some query might be missing, they are loaded by the next page
+        let next_pos = self.page.as_ref().and_then(|page| page.next_pos());
+        if let Some(next_pos) = next_pos {
+            for batch in &mut batch {
+                cursor::retain(batch, |pos| pos < next_pos);
+            }
+        }
         Ok(BatchResponse {
             data: batch,
             last_block: to_block,
+            next_pos,
         })
     }
 
@@ -425,7 +521,7 @@ impl BatchLoader {
         &self,
         from_block: i32,
         to_block: i32,
-        selections: &Vec<CallSelection>,
+        selections: &[CallSelection],
     ) -> Result<Vec<Call>, Error> {
         if selections.is_empty() {
             return Ok(vec![]);
@@ -440,44 +536,20 @@ impl BatchLoader {
         let to_block = format!("{:010}", to_block + 1);
 
         let mut params = Parameters::default();
-        let mut query = select([
-            "id",
-            "parent_id",
-            "block_id",
-            "extrinsic_id",
-            "name",
-            "args",
-            "success",
-            "error",
-            "origin",
-            "pos::int8",
-        ])
-        .from("call")
-        .where_(format!("block_id > {}::char(10)", params.add(&from_block)))
-        .where_(format!("block_id < {}::char(10)", params.add(&to_block)));
+        let mut query = select(CALL_COLUMNS)
+            .from("call")
+            .where_(format!("block_id > {}::char(10)", params.add(&from_block)))
+            .where_(format!("block_id < {}::char(10)", params.add(&to_block)));
         if !wildcard {
             query = query.where_(format!("name = ANY({})", params.add(&names)));
         }
+        query = paginate(query, &mut params, self.page.as_deref());
         let mut calls = sqlx::query_as_with::<_, Call, _>(&query.to_string(), params.get())
-            .fetch_all(&self.pool)
+            .fetch_all(&mut *self.session.acquire().await?)
             .observe_duration("call")
             .await?;
+        self.observe_page(calls.iter().map(|call| call.pos));
 
-        let query = select([
-            "id",
-            "parent_id",
-            "block_id",
-            "extrinsic_id",
-            "name",
-            "args",
-            "success",
-            "error",
-            "origin",
-            "pos::int8",
-        ])
-        .from("call")
-        .where_("id = ANY($1::varchar(30)[])")
-        .to_string();
         let mut parents_ids: Vec<String> = calls
             .iter()
             .filter_map(|call| call.parent_id.clone())
@@ -497,9 +569,9 @@ impl BatchLoader {
                 })
                 .collect();
             if !to_load.is_empty() {
-                let mut parents = sqlx::query_as::<_, Call>(&query)
-                    .bind(to_load)
-                    .fetch_all(&self.pool)
+                let (query, args) = calls_by_ids(&to_load, None);
+                let mut parents = sqlx::query_as_with::<_, Call, _>(&query, args)
+                    .fetch_all(&mut *self.session.acquire().await?)
                     .observe_duration("call")
                     .await?;
                 calls.append(&mut parents);
@@ -518,36 +590,9 @@ impl BatchLoader {
     }
 
     async fn load_calls_by_ids(&self, ids: &[String]) -> Result<Vec<Call>, Error> {
-        // i inject ids into the query otherwise it executes so long
-        let ids = ids
-            .iter()
-            .map(|id| format!("'{}'", id))
-            .collect::<Vec<String>>()
-            .join(", ");
-        let query = format!(
-            "WITH RECURSIVE recursive_call AS (
-                SELECT * FROM call WHERE id IN ({})
-                UNION ALL
-                SELECT DISTINCT ON (call.id) call.*
-                FROM call JOIN recursive_call ON recursive_call.parent_id = call.id
-            )
-            SELECT
-                id,
-                parent_id,
-                block_id,
-                extrinsic_id,
-                name,
-                args,
-                success,
-                error,
-                origin,
-                pos::int8
-            FROM recursive_call
-            ORDER BY block_id",
-            ids
-        );
-        let calls = sqlx::query_as::<_, Call>(&query)
-            .fetch_all(&self.pool)
+        let (query, args) = calls_with_parents_by_ids(ids);
+        let calls = sqlx::query_as_with::<_, Call, _>(&query, args)
+            .fetch_all(&mut *self.session.acquire().await?)
             .observe_duration("call")
             .await?;
         Ok(calls)
@@ -557,7 +602,7 @@ impl BatchLoader {
         &self,
         from_block: i32,
         to_block: i32,
-        selections: &Vec<EventSelection>,
+        selections: &[EventSelection],
     ) -> Result<Vec<Event>, Error> {
         if selections.is_empty() {
             return Ok(vec![]);
@@ -565,46 +610,34 @@ impl BatchLoader {
         let wildcard = selections.iter().any(|selection| selection.name == "*");
         let names = selections
             .iter()
-            .map(|selection| format!("'{}'", selection.name))
-            .collect::<Vec<String>>()
-            .join(", ");
+            .map(|selection| selection.name.clone())
+            .collect::<Vec<String>>();
 
         let from_block = format!("{:010}", from_block);
         let to_block = format!("{:010}", to_block + 1);
 
-        let table = match self.database_type {
-            DatabaseType::Cockroach => {
-                if wildcard {
-                    "event"
-                } else {
-                    "event@idx_event__name__block"
-                }
-            }
-            DatabaseType::Postgres => "event",
+        // index doesn't help to scan all events
+        let table = if wildcard {
+            "event".to_string()
+        } else {
+            self.index_hints
+                .table(QueryShape::EventByName, &self.database_type)
         };
 
         let mut params = Parameters::default();
-        let mut query = select([
-            "id",
-            "block_id",
-            "index_in_block::int8",
-            "phase",
-            "extrinsic_id",
-            "call_id",
-            "name",
-            "args",
-            "pos::int8",
-        ])
-        .from(table)
-        .where_(format!("block_id > {}::char(10)", params.add(&from_block)))
-        .where_(format!("block_id < {}::char(10)", params.add(&to_block)));
+        let mut query = select(EVENT_COLUMNS)
+            .from(table)
+            .where_(format!("block_id > {}::char(10)", params.add(&from_block)))
+            .where_(format!("block_id < {}::char(10)", params.add(&to_block)));
         if !wildcard {
-            query = query.where_(format!("name IN ({})", &names));
+            query = query.where_(format!("name = ANY({})", params.add(&names)));
         }
+        query = paginate(query, &mut params, self.page.as_deref());
         let events = sqlx::query_as_with::<_, Event, _>(&query.to_string(), params.get())
-            .fetch_all(&self.pool)
+            .fetch_all(&mut *self.session.acquire().await?)
             .observe_duration("event")
             .await?;
+        self.observe_page(events.iter().map(|event| event.pos));
         Ok(events)
     }
 
@@ -612,7 +645,7 @@ impl BatchLoader {
         &self,
         from_block: i32,
         to_block: i32,
-        selections: &Vec<GearMessageEnqueuedSelection>,
+        selections: &[GearMessageEnqueuedSelection],
     ) -> Result<Vec<Event>, Error> {
         if selections.is_empty() {
             return Ok(Vec::new());
@@ -625,23 +658,24 @@ impl BatchLoader {
         let from_block = format!("{:010}", from_block);
         let to_block = format!("{:010}", to_block + 1);
 
-        let query = "SELECT event_id
-            FROM gear_message_enqueued
-            WHERE program = ANY($1) AND event_id > $2 AND event_id < $3
-            ORDER BY event_id";
-        let ids = sqlx::query_scalar::<_, String>(&query)
-            .bind(&programs)
-            .bind(&from_block)
-            .bind(&to_block)
-            .fetch_all(&self.pool)
+        let mut params = Parameters::default();
+        let query = select(["event_id"])
+            .from("gear_message_enqueued")
+            .where_(format!("program = ANY({})", params.add(&programs)))
+            .where_(format!("event_id > {}", params.add(&from_block)))
+            .where_(format!("event_id < {}", params.add(&to_block)))
+            .order_by("event_id");
+        let ids = sqlx::query_scalar_with::<_, String, _>(&query.to_string(), params.get())
+            .fetch_all(&mut *self.session.acquire().await?)
             .observe_duration("gear_message_enqueued")
             .await?;
 
-        let events = sqlx::query_as::<_, Event>(EVENTS_BY_ID_QUERY)
-            .bind(&ids)
-            .fetch_all(&self.pool)
+        let (query, args) = events_by_ids(&ids, self.page.as_deref());
+        let events = sqlx::query_as_with::<_, Event, _>(&query, args)
+            .fetch_all(&mut *self.session.acquire().await?)
             .observe_duration("event")
             .await?;
+        self.observe_page(events.iter().map(|event| event.pos));
         Ok(events)
     }
 
@@ -649,7 +683,7 @@ impl BatchLoader {
         &self,
         from_block: i32,
         to_block: i32,
-        selections: &Vec<GearUserMessageSentSelection>,
+        selections: &[GearUserMessageSentSelection],
     ) -> Result<Vec<Event>, Error> {
         if selections.is_empty() {
             return Ok(Vec::new());
@@ -662,23 +696,24 @@ impl BatchLoader {
         let from_block = format!("{:010}", from_block);
         let to_block = format!("{:010}", to_block + 1);
 
-        let query = "SELECT event_id
-            FROM gear_user_message_sent
-            WHERE program = ANY($1) AND event_id > $2 AND event_id < $3
-            ORDER BY event_id";
-        let ids = sqlx::query_scalar::<_, String>(&query)
-            .bind(&programs)
-            .bind(&from_block)
-            .bind(&to_block)
-            .fetch_all(&self.pool)
+        let mut params = Parameters::default();
+        let query = select(["event_id"])
+            .from("gear_user_message_sent")
+            .where_(format!("program = ANY({})", params.add(&programs)))
+            .where_(format!("event_id > {}", params.add(&from_block)))
+            .where_(format!("event_id < {}", params.add(&to_block)))
+            .order_by("event_id");
+        let ids = sqlx::query_scalar_with::<_, String, _>(&query.to_string(), params.get())
+            .fetch_all(&mut *self.session.acquire().await?)
             .observe_duration("gear_user_message_sent")
             .await?;
 
-        let events = sqlx::query_as::<_, Event>(EVENTS_BY_ID_QUERY)
-            .bind(&ids)
-            .fetch_all(&self.pool)
+        let (query, args) = events_by_ids(&ids, self.page.as_deref());
+        let events = sqlx::query_as_with::<_, Event, _>(&query, args)
+            .fetch_all(&mut *self.session.acquire().await?)
             .observe_duration("event")
             .await?;
+        self.observe_page(events.iter().map(|event| event.pos));
         Ok(events)
     }
 
@@ -706,18 +741,17 @@ impl BatchLoader {
         let from_block = format!("{:010}", from_block);
         let to_block = format!("{:010}", to_block + 1);
 
-        let query = format!(
-            "SELECT event_id
-            FROM {}
-            WHERE contract = $1 AND event_id > $2 AND event_id < $3
-            ORDER BY event_id",
-            event_table
-        );
-        let ids = sqlx::query_scalar::<_, String>(&query)
-            .bind(&selection.contract)
-            .bind(&from_block)
-            .bind(&to_block)
-            .fetch_all(&self.pool)
+        let mut params = Parameters::default();
+        let mut query = select(["event_id"])
+            .from(event_table)
+            .where_(format!("event_id > {}", params.add(&from_block)))
+            .where_(format!("event_id < {}", params.add(&to_block)));
+        if selection.contract != "*" {
+            query = query.where_(format!("contract = {}", params.add(&selection.contract)));
+        }
+        query = query.order_by("event_id");
+        let ids = sqlx::query_scalar_with::<_, String, _>(&query.to_string(), params.get())
+            .fetch_all(&mut *self.session.acquire().await?)
             .observe_duration(event_table)
             .await?;
         Ok(ids)
@@ -760,21 +794,23 @@ impl BatchLoader {
             query = query.order_by("id");
             let mut log_ids =
                 sqlx::query_scalar_with::<_, String, _>(&query.to_string(), params.get())
-                    .fetch_all(&self.pool)
+                    .fetch_all(&mut *self.session.acquire().await?)
                     .observe_duration(log_table)
                     .await?;
             ids.append(&mut log_ids);
         }
         ids.sort();
         ids.dedup();
-        let query = "SELECT event_id
-            FROM acala_evm_executed_log
-            WHERE id = ANY($1::char(23)[])";
-        let selection_ids = sqlx::query_scalar::<_, String>(query)
-            .bind(&ids)
-            .fetch_all(&self.pool)
-            .observe_duration("acala_evm_executed_log")
-            .await?;
+        let mut params = Parameters::default();
+        let query = select(["event_id"]).from(log_table).where_(format!(
+            "id = ANY({})",
+            cast(params.add(&ids), "char(23)[]")
+        ));
+        let selection_ids =
+            sqlx::query_scalar_with::<_, String, _>(&query.to_string(), params.get())
+                .fetch_all(&mut *self.session.acquire().await?)
+                .observe_duration(log_table)
+                .await?;
         Ok(selection_ids)
     }
 
@@ -802,11 +838,12 @@ impl BatchLoader {
         }
         ids.sort();
         ids.dedup();
-        let events = sqlx::query_as::<_, Event>(EVENTS_BY_ID_QUERY)
-            .bind(&ids)
-            .fetch_all(&self.pool)
+        let (query, args) = events_by_ids(&ids, self.page.as_deref());
+        let events = sqlx::query_as_with::<_, Event, _>(&query, args)
+            .fetch_all(&mut *self.session.acquire().await?)
             .observe_duration("event")
             .await?;
+        self.observe_page(events.iter().map(|event| event.pos));
         Ok(events)
     }
 
@@ -814,7 +851,7 @@ impl BatchLoader {
         &self,
         from_block: i32,
         to_block: i32,
-        selections: &Vec<ContractsEventSelection>,
+        selections: &[ContractsEventSelection],
     ) -> Result<Vec<Event>, Error> {
         if selections.is_empty() {
             return Ok(Vec::new());
@@ -838,22 +875,23 @@ impl BatchLoader {
         }
         query = query.order_by("event_id");
         let ids = sqlx::query_scalar_with::<_, String, _>(&query.to_string(), params.get())
-            .fetch_all(&self.pool)
+            .fetch_all(&mut *self.session.acquire().await?)
             .observe_duration("contracts_contract_emitted")
             .await?;
 
-        let events = sqlx::query_as::<_, Event>(EVENTS_BY_ID_QUERY)
-            .bind(&ids)
-            .fetch_all(&self.pool)
+        let (query, args) = events_by_ids(&ids, self.page.as_deref());
+        let events = sqlx::query_as_with::<_, Event, _>(&query, args)
+            .fetch_all(&mut *self.session.acquire().await?)
             .observe_duration("event")
             .await?;
+        self.observe_page(events.iter().map(|event| event.pos));
         Ok(events)
     }
 
     fn group_evm_selections<'a>(
         &'a self,
         selections: &'a Vec<EvmLogSelection>,
-    ) -> Vec<Vec<&EvmLogSelection>> {
+    ) -> Vec<Vec<&'a EvmLogSelection>> {
         let mut grouped: Vec<Vec<&EvmLogSelection>> = vec![];
         for selection in selections {
             let group = grouped.iter_mut().find(|group| {
@@ -894,20 +932,16 @@ impl BatchLoader {
                 .map(|selection| selection.contract.clone())
                 .collect();
 
-            let table = match self.database_type {
-                DatabaseType::Cockroach => {
-                    let has_topics = if let Some(topics) = selections[0].filter.get(0) {
-                        !topics.is_empty()
-                    } else {
-                        false
-                    };
-                    if !wildcard && has_topics {
-                        "frontier_evm_log@idx_evm_log__contract__topic0__event"
-                    } else {
-                        "frontier_evm_log"
-                    }
-                }
-                DatabaseType::Postgres => "frontier_evm_log",
+            let has_topics = if let Some(topics) = selections[0].filter.first() {
+                !topics.is_empty()
+            } else {
+                false
+            };
+            let table = if !wildcard && has_topics {
+                self.index_hints
+                    .table(QueryShape::EvmLogByContractTopic, &self.database_type)
+            } else {
+                "frontier_evm_log".to_string()
             };
             let mut params = Parameters::default();
             let mut query = select(["event_id"])
@@ -915,7 +949,7 @@ impl BatchLoader {
                 .where_(format!("event_id > {}", params.add(&from_block)))
                 .where_(format!("event_id < {}", params.add(&to_block)));
             if !wildcard {
-                query = query.where_(&format!(
+                query = query.where_(format!(
                     "contract = ANY({}::char(42)[])",
                     params.add(&contracts),
                 ));
@@ -934,31 +968,19 @@ impl BatchLoader {
             query = query.order_by("event_id");
             let mut log_ids =
                 sqlx::query_scalar_with::<_, String, _>(&query.to_string(), params.get())
-                    .fetch_all(&self.pool)
+                    .fetch_all(&mut *self.session.acquire().await?)
                     .observe_duration("frontier_evm_log")
                     .await?;
             ids.append(&mut log_ids);
         }
         ids.sort();
         ids.dedup();
-        let query = "SELECT
-                event.id,
-                event.block_id,
-                event.index_in_block::int8,
-                event.phase,
-                event.extrinsic_id,
-                event.call_id,
-                event.name,
-                event.args,
-                event.pos::int8,
-                '' AS evm_tx_hash
-            FROM event
-            WHERE event.id = ANY($1::char(23)[])";
-        let mut logs = sqlx::query_as::<_, EvmLog>(query)
-            .bind(&ids)
-            .fetch_all(&self.pool)
+        let (query, args) = evm_logs_by_ids(&ids, self.page.as_deref());
+        let mut logs = sqlx::query_as_with::<_, EvmLog, _>(&query, args)
+            .fetch_all(&mut *self.session.acquire().await?)
             .observe_duration("event")
             .await?;
+        self.observe_page(logs.iter().map(|log| log.pos));
 
         let mut extrinsics = logs
             .iter()
@@ -966,17 +988,9 @@ impl BatchLoader {
             .collect::<Vec<String>>();
         extrinsics.sort();
         extrinsics.dedup();
-        let query = "SELECT
-                extrinsic_id,
-                COALESCE(
-                    jsonb_extract_path_text(args, '2'),
-                    jsonb_extract_path_text(args, 'transactionHash')
-                ) AS evm_tx_hash
-            FROM event
-            WHERE name = 'Ethereum.Executed' AND extrinsic_id = ANY($1::char(23)[])";
-        let tx_hashes = sqlx::query_as::<_, (String, String)>(query)
-            .bind(&extrinsics)
-            .fetch_all(&self.pool)
+        let (query, args) = evm_tx_hashes_by_extrinsic_ids(&extrinsics);
+        let tx_hashes = sqlx::query_as_with::<_, (String, String), _>(&query, args)
+            .fetch_all(&mut *self.session.acquire().await?)
             .await?;
         let mut hash_by_extrinsic: HashMap<String, String> = HashMap::new();
         for (extrinsic_id, hash) in tx_hashes {
@@ -1008,15 +1022,13 @@ impl BatchLoader {
             let from_block = format!("{:010}", from_block);
             let to_block = format!("{:010}", to_block + 1);
 
-            let table = match self.database_type {
-                DatabaseType::Cockroach => {
-                    if selection.sighash.is_some() && selection.contract != "*" {
-                        "frontier_ethereum_transaction@IDX_frontier_ethereum_transaction__contract__sighash__call"
-                    } else {
-                        "frontier_ethereum_transaction"
-                    }
-                }
-                DatabaseType::Postgres => "frontier_ethereum_transaction",
+            let table = if selection.sighash.is_some() && selection.contract != "*" {
+                self.index_hints.table(
+                    QueryShape::EthTransactionByContractSighash,
+                    &self.database_type,
+                )
+            } else {
+                "frontier_ethereum_transaction".to_string()
             };
 
             let mut params = Parameters::default();
@@ -1034,49 +1046,26 @@ impl BatchLoader {
 
             let sql = query.to_string();
             let mut selection_ids = sqlx::query_scalar_with::<_, String, _>(&sql, params.get())
-                .fetch_all(&self.pool)
+                .fetch_all(&mut *self.session.acquire().await?)
                 .observe_duration("frontier_ethereum_transaction")
                 .await?;
             ids.append(&mut selection_ids);
         }
         ids.sort();
         ids.dedup();
-        let query = "SELECT
-                id,
-                parent_id,
-                block_id,
-                extrinsic_id,
-                name,
-                args,
-                success,
-                error,
-                origin,
-                pos::int8
-            FROM call WHERE id = ANY($1::varchar(30)[])";
-        let mut calls = sqlx::query_as::<_, Call>(query)
-            .bind(&ids)
-            .fetch_all(&self.pool)
+        let (query, args) = calls_by_ids(&ids, self.page.as_deref());
+        let mut calls = sqlx::query_as_with::<_, Call, _>(&query, args)
+            .fetch_all(&mut *self.session.acquire().await?)
             .observe_duration("call")
             .await?;
+        self.observe_page(calls.iter().map(|call| call.pos));
 
         let events = if ids.is_empty() {
             vec![]
         } else {
-            let query = "SELECT
-                    id,
-                    block_id,
-                    index_in_block::int8,
-                    phase,
-                    extrinsic_id,
-                    call_id,
-                    name,
-                    args,
-                    pos::int8
-                FROM event
-                WHERE call_id = ANY($1::char(30)[]) AND name = 'Ethereum.Executed'";
-            sqlx::query_as::<_, Event>(query)
-                .bind(&ids)
-                .fetch_all(&self.pool)
+            let (query, args) = executed_events_by_call_ids(&ids);
+            sqlx::query_as_with::<_, Event, _>(&query, args)
+                .fetch_all(&mut *self.session.acquire().await?)
                 .observe_duration("event")
                 .await?
         };
@@ -1100,9 +1089,9 @@ impl BatchLoader {
                 })
                 .collect();
             if !to_load.is_empty() {
-                let mut parents = sqlx::query_as::<_, Call>(query)
-                    .bind(to_load)
-                    .fetch_all(&self.pool)
+                let (query, args) = calls_by_ids(&to_load, None);
+                let mut parents = sqlx::query_as_with::<_, Call, _>(&query, args)
+                    .fetch_all(&mut *self.session.acquire().await?)
                     .observe_duration("call")
                     .await?;
                 calls.append(&mut parents);
@@ -1120,68 +1109,39 @@ impl BatchLoader {
         Ok((calls, events))
     }
 
+    fn observe_page(&self, positions: impl ExactSizeIterator<Item = i64>) {
+        if let Some(page) = &self.page {
+            page.observe(positions);
+        }
+    }
+
     async fn load_blocks(&self, from_block: i32, to_block: i32) -> Result<Vec<BlockHeader>, Error> {
-        let query = "SELECT
-                id,
-                height::int8,
-                hash,
-                parent_hash,
-                state_root,
-                extrinsics_root,
-                timestamp,
-                spec_id,
-                validator
-            FROM block
-            WHERE height >= $1 AND height <= $2
-            ORDER BY height";
-        let blocks = sqlx::query_as::<_, BlockHeader>(&query)
-            .bind(from_block)
-            .bind(to_block)
-            .fetch_all(&self.pool)
+        let mut params = Parameters::default();
+        let query = select(BLOCK_COLUMNS)
+            .from("block")
+            .where_(format!("height >= {}", params.add(from_block)))
+            .where_(format!("height <= {}", params.add(to_block)))
+            .order_by("height");
+        let blocks = sqlx::query_as_with::<_, BlockHeader, _>(&query.to_string(), params.get())
+            .fetch_all(&mut *self.session.acquire().await?)
             .observe_duration("block")
             .await?;
         Ok(blocks)
     }
 
-    async fn load_blocks_by_ids(&self, ids: &Vec<String>) -> Result<Vec<BlockHeader>, Error> {
-        let query = "SELECT
-                id,
-                height::int8,
-                hash,
-                parent_hash,
-                state_root,
-                extrinsics_root,
-                timestamp,
-                spec_id,
-                validator
-            FROM block
-            WHERE id = ANY($1::char(16)[])";
-        let blocks = sqlx::query_as::<_, BlockHeader>(query)
-            .bind(ids)
-            .fetch_all(&self.pool)
+    async fn load_blocks_by_ids(&self, ids: &[String]) -> Result<Vec<BlockHeader>, Error> {
+        let (query, args) = blocks_by_ids(ids);
+        let blocks = sqlx::query_as_with::<_, BlockHeader, _>(&query, args)
+            .fetch_all(&mut *self.session.acquire().await?)
             .observe_duration("block")
             .await?;
         Ok(blocks)
     }
 
-    async fn load_extrinsics(&self, ids: &Vec<String>) -> Result<Vec<Extrinsic>, Error> {
-        let query = "SELECT
-                id,
-                block_id,
-                index_in_block::int8,
-                version::int8,
-                signature,
-                success,
-                error,
-                call_id,
-                fee,
-                tip,
-                hash,
-                pos::int8
-            FROM extrinsic WHERE id = ANY($1::char(23)[])";
-        let extrinsics = sqlx::query_as::<_, Extrinsic>(query)
-            .bind(ids)
-            .fetch_all(&self.pool)
+    async fn load_extrinsics(&self, ids: &[String]) -> Result<Vec<Extrinsic>, Error> {
+        let (query, args) = extrinsics_by_ids(ids);
+        let extrinsics = sqlx::query_as_with::<_, Extrinsic, _>(&query, args)
+            .fetch_all(&mut *self.session.acquire().await?)
             .observe_duration("extrinsic")
             .await?;
         Ok(extrinsics)
@@ -1194,11 +1154,12 @@ impl BatchLoader {
         mut calls_by_block: HashMap<String, Vec<serde_json::Value>>,
         mut extrinsics_by_block: HashMap<String, Vec<serde_json::Value>>,
         logs_by_block: HashMap<String, Vec<serde_json::Value>>,
+        mut relations: HashMap<String, Relations>,
     ) -> Vec<Batch> {
         for (block_id, mut data) in logs_by_block.into_iter() {
             events_by_block
                 .entry(block_id)
-                .or_insert_with(Vec::new)
+                .or_default()
                 .append(&mut data);
         }
         blocks
@@ -1222,6 +1183,7 @@ impl BatchLoader {
                     extrinsics: extrinsics_by_block.remove(&block.id).unwrap_or_default(),
                     calls: calls_by_block.remove(&block.id).unwrap_or_default(),
                     events: deduplicated_events,
+                    relations: relations.remove(&block.id).unwrap_or_default(),
                     header: block,
                 }
             })
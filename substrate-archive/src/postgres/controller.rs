@@ -1,33 +1,52 @@
-use super::batch::BatchLoader;
+use super::batch::{BatchLoader, Page};
+use super::density::DensityModel;
+use super::hints::IndexHints;
 use super::partial::{PartialBatchLoader, PartialOptions};
+use super::session::Session;
 use super::{BatchResponse, DatabaseType};
 use crate::archive::BatchOptions;
+use crate::budget::{self, size_of_batch, ResponseSizeLimit};
+use crate::cursor;
 use crate::error::Error;
 use crate::metrics::ObserverExt;
-use sqlx::{Pool, Postgres};
+use std::sync::Arc;
+use std::time::Duration;
 
 pub struct BatchController {
-    pool: Pool<Postgres>,
+    session: Session,
     database_type: DatabaseType,
+    index_hints: IndexHints,
     scan_start_value: u16,
     scan_max_value: u32,
     scan_time_limit: u16,
+    max_scan_time_limit: u16,
+    response_size_limit: ResponseSizeLimit,
+    density: Arc<DensityModel>,
 }
 
 impl BatchController {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
-        pool: Pool<Postgres>,
+        session: Session,
         database_type: DatabaseType,
+        index_hints: IndexHints,
         scan_start_value: u16,
         scan_max_value: u32,
         scan_time_limit: u16,
+        max_scan_time_limit: u16,
+        response_size_limit: ResponseSizeLimit,
+        density: Arc<DensityModel>,
     ) -> BatchController {
         BatchController {
-            pool,
+            session,
             database_type,
+            index_hints,
             scan_start_value,
             scan_max_value,
             scan_time_limit,
+            max_scan_time_limit,
+            response_size_limit,
+            density,
         }
     }
 
@@ -45,12 +64,14 @@ impl BatchController {
                                 return Ok(BatchResponse {
                                     data: vec![],
                                     next_block: None,
+                                    next_pos: None,
                                 })
                             }
                             None => {
                                 return Ok(BatchResponse {
                                     data: vec![],
                                     next_block: Some(options.from_block),
+                                    next_pos: None,
                                 })
                             }
                         }
@@ -59,34 +80,126 @@ impl BatchController {
             }
         };
 
-        match options.limit {
+        let response_size_limit = self.response_size_limit.resolve(options.max_bytes);
+        let mut response = match options.limit {
             Some(..) => {
-                let strategy = options.loader(self.pool.clone(), self.database_type.clone());
-                strategy.load().await
-            }
-            None => {
-                let loader = BatchLoader::new(self.pool.clone(), self.database_type.clone());
-                let strategy = PartialBatchLoader::new(
-                    loader,
-                    self.scan_start_value,
-                    self.scan_max_value,
-                    self.scan_time_limit,
+                let strategy = options.loader(
+                    self.session.clone(),
+                    self.database_type.clone(),
+                    self.index_hints.clone(),
                 );
-                let options = PartialOptions {
-                    from_block: options.from_block,
-                    to_block,
-                    include_all_blocks: options.include_all_blocks,
-                    selections: options.selections.clone(),
-                };
-                strategy.load(&options).await
+                strategy.load().await?
             }
+            None => match options.from_pos {
+                Some(from_pos) if options.from_block <= options.scan_end(to_block) => {
+                    self.resume(options, from_pos, to_block, response_size_limit)
+                        .await?
+                }
+                _ => {
+                    self.scan(options, options.from_block, to_block, response_size_limit)
+                        .await?
+                }
+            },
+        };
+        cursor::apply(options, &mut response, response_size_limit);
+        Ok(response)
+    }
+
+    fn loader(&self) -> BatchLoader {
+        BatchLoader::new(
+            self.session.clone(),
+            self.database_type.clone(),
+            self.index_hints.clone(),
+        )
+    }
+
+    async fn scan(
+        &self,
+        options: &BatchOptions,
+        from_block: i32,
+        to_block: i32,
+        response_size_limit: usize,
+    ) -> Result<BatchResponse, Error> {
+        let strategy = PartialBatchLoader::new(
+            self.loader(),
+            self.scan_start_value,
+            self.scan_max_value,
+            self.density.clone(),
+        );
+        let options = PartialOptions {
+            from_block,
+            to_block: options.scan_end(to_block),
+            include_all_blocks: options.include_all_blocks,
+            response_size_limit,
+            time_limit: self.time_limit(options.time_limit_ms),
+            selections: options.selections.clone(),
+            progress: options.progress.clone(),
+        };
+        strategy.load(&options).await
+    }
+
+    // Only items of `from_block` starting at `from_pos` are queried and no more
+    // of them than the budget can take, so that the following pages of a huge
+    // block don't load it over and over again. The rest of the range is scanned as usual.
+    async fn resume(
+        &self,
+        options: &BatchOptions,
+        from_pos: i64,
+        to_block: i32,
+        response_size_limit: usize,
+    ) -> Result<BatchResponse, Error> {
+        let page = Page::new(from_pos, budget::rows_for(response_size_limit));
+        let first = self
+            .loader()
+            .page(page)
+            .load(
+                options.from_block,
+                options.from_block,
+                options.include_all_blocks,
+                &options.selections,
+            )
+            .await?;
+        let mut data = first.data;
+        if first.next_pos.is_some() {
+            return Ok(BatchResponse {
+                data,
+                next_block: Some(options.from_block),
+                next_pos: first.next_pos,
+            });
         }
+
+        let size = size_of_batch(&data);
+        if size > response_size_limit || options.from_block == options.scan_end(to_block) {
+            return Ok(BatchResponse {
+                data,
+                next_block: Some(options.from_block + 1),
+                next_pos: None,
+            });
+        }
+        let mut response = self
+            .scan(
+                options,
+                options.from_block + 1,
+                to_block,
+                response_size_limit - size,
+            )
+            .await?;
+        data.append(&mut response.data);
+        response.data = data;
+        Ok(response)
+    }
+
+    fn time_limit(&self, requested: Option<u32>) -> Duration {
+        let time_limit = requested
+            .unwrap_or_else(|| self.scan_time_limit.into())
+            .min(self.max_scan_time_limit.into());
+        Duration::from_millis(time_limit.into())
     }
 
     async fn archive_head(&self) -> Result<Option<i64>, Error> {
         let query = "SELECT height::int8 FROM block ORDER BY height DESC LIMIT 1";
         let head = sqlx::query_scalar::<_, i64>(query)
-            .fetch_optional(&self.pool)
+            .fetch_optional(&mut *self.session.acquire().await?)
             .observe_duration("block")
             .await?;
         Ok(head)
@@ -0,0 +1,201 @@
+use crate::archive::Selections;
+use crate::metrics::{SCAN_RANGE_MODEL_ENTRIES, SCAN_RANGE_MODEL_LOOKUPS};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+
+// blocks sharing the same learned range width
+const REGION_SIZE: i32 = 100_000;
+const CAPACITY: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Key {
+    fingerprint: u64,
+    region: i32,
+}
+
+impl Key {
+    pub fn new(selections: &Selections, include_all_blocks: bool, block: i32) -> Key {
+        Key {
+            fingerprint: fingerprint(selections, include_all_blocks),
+            region: block / REGION_SIZE,
+        }
+    }
+
+    /// Key of the same selections for a region containing `block`.
+    pub fn at(&self, block: i32) -> Key {
+        Key {
+            fingerprint: self.fingerprint,
+            region: block / REGION_SIZE,
+        }
+    }
+}
+
+// identifies what is selected, requested fields don't affect data density
+fn fingerprint(selections: &Selections, include_all_blocks: bool) -> u64 {
+    let mut items: Vec<String> = vec![];
+    items.extend(selections.call.iter().map(|s| format!("call:{}", s.name)));
+    items.extend(selections.event.iter().map(|s| format!("event:{}", s.name)));
+    items.extend(
+        selections
+            .evm_log
+            .iter()
+            .map(|s| format!("evm_log:{}:{:?}", s.contract, s.filter)),
+    );
+    items.extend(
+        selections
+            .eth_transact
+            .iter()
+            .map(|s| format!("eth_transact:{}:{:?}", s.contract, s.sighash)),
+    );
+    items.extend(
+        selections
+            .contracts_event
+            .iter()
+            .map(|s| format!("contracts_event:{}", s.contract)),
+    );
+    items.extend(
+        selections
+            .gear_message_enqueued
+            .iter()
+            .map(|s| format!("gear_message_enqueued:{}", s.program)),
+    );
+    items.extend(
+        selections
+            .gear_user_message_sent
+            .iter()
+            .map(|s| format!("gear_user_message_sent:{}", s.program)),
+    );
+    items.extend(
+        selections
+            .acala_evm_executed
+            .iter()
+            .map(|s| format!("acala_evm_executed:{}:{:?}", s.contract, s.logs)),
+    );
+    items.extend(
+        selections
+            .acala_evm_executed_failed
+            .iter()
+            .map(|s| format!("acala_evm_executed_failed:{}:{:?}", s.contract, s.logs)),
+    );
+    items.sort();
+    items.dedup();
+
+    let mut hasher = DefaultHasher::new();
+    items.hash(&mut hasher);
+    include_all_blocks.hash(&mut hasher);
+    hasher.finish()
+}
+
+struct Entry {
+    range_width: i32,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct Entries {
+    map: HashMap<Key, Entry>,
+    clock: u64,
+}
+
+/// Range widths learned by partial scans, shared across requests.
+///
+/// Keeps at most a fixed number of entries evicting the least recently used one.
+pub struct DensityModel {
+    entries: Mutex<Entries>,
+    capacity: usize,
+}
+
+impl Default for DensityModel {
+    fn default() -> DensityModel {
+        DensityModel::new(CAPACITY)
+    }
+}
+
+impl DensityModel {
+    pub fn new(capacity: usize) -> DensityModel {
+        DensityModel {
+            entries: Mutex::new(Entries::default()),
+            capacity,
+        }
+    }
+
+    pub fn get(&self, key: &Key) -> Option<i32> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.clock += 1;
+        let clock = entries.clock;
+        let range_width = entries.map.get_mut(key).map(|entry| {
+            entry.last_used = clock;
+            entry.range_width
+        });
+        let result = if range_width.is_some() { "hit" } else { "miss" };
+        SCAN_RANGE_MODEL_LOOKUPS.with_label_values(&[result]).inc();
+        range_width
+    }
+
+    pub fn set(&self, key: Key, range_width: i32) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.clock += 1;
+        let last_used = entries.clock;
+        if !entries.map.contains_key(&key) && entries.map.len() >= self.capacity {
+            let oldest = entries
+                .map
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| *key);
+            if let Some(oldest) = oldest {
+                entries.map.remove(&oldest);
+            }
+        }
+        entries.map.insert(
+            key,
+            Entry {
+                range_width,
+                last_used,
+            },
+        );
+        SCAN_RANGE_MODEL_ENTRIES.set(entries.map.len() as i64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DensityModel, Key};
+    use crate::archive::Selections;
+    use crate::selection::{EventDataSelection, EventSelection};
+
+    fn event_selections(name: &str) -> Selections {
+        Selections {
+            event: vec![EventSelection {
+                name: name.to_string(),
+                data: EventDataSelection::new(false),
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn key_ignores_requested_fields() {
+        let mut selections = event_selections("Balances.Transfer");
+        let key = Key::new(&selections, false, 10);
+        selections.event[0].data = EventDataSelection::new(true);
+        assert!(key == Key::new(&selections, false, 99_999));
+        assert!(key != Key::new(&selections, false, 100_000));
+        assert!(key != Key::new(&selections, true, 10));
+        assert!(key != Key::new(&event_selections("System.Remarked"), false, 10));
+    }
+
+    #[test]
+    fn least_recently_used_entry_evicted() {
+        let model = DensityModel::new(2);
+        let key = Key::new(&event_selections("Balances.Transfer"), false, 0);
+        model.set(key.at(0), 100);
+        model.set(key.at(100_000), 200);
+        assert!(model.get(&key.at(0)) == Some(100));
+        model.set(key.at(200_000), 300);
+        assert!(model.get(&key.at(100_000)).is_none());
+        assert!(model.get(&key.at(0)) == Some(100));
+        assert!(model.get(&key.at(200_000)) == Some(300));
+    }
+}
@@ -0,0 +1,111 @@
+use super::DatabaseType;
+use crate::error::Error;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Query which may benefit from an explicit index hint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryShape {
+    EventByName,
+    EvmLogByContractTopic,
+    EthTransactionByContractSighash,
+}
+
+impl QueryShape {
+    fn table(&self) -> &'static str {
+        match self {
+            QueryShape::EventByName => "event",
+            QueryShape::EvmLogByContractTopic => "frontier_evm_log",
+            QueryShape::EthTransactionByContractSighash => "frontier_ethereum_transaction",
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+struct Config {
+    // only to report that PostgreSQL has no index hints
+    #[serde(default)]
+    postgres: HashMap<QueryShape, Option<String>>,
+    #[serde(default)]
+    cockroach: HashMap<QueryShape, Option<String>>,
+}
+
+/// Indexes to be used in `table@index` hints of CockroachDB queries,
+/// PostgreSQL has no such syntax so its queries go without hints.
+///
+/// Hints can be overridden with a JSON config where `null` removes a hint
+/// ```json
+/// {
+///     "cockroach": {
+///         "event_by_name": "idx_event__name__block",
+///         "evm_log_by_contract_topic": null
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct IndexHints {
+    cockroach: HashMap<QueryShape, String>,
+}
+
+impl Default for IndexHints {
+    fn default() -> IndexHints {
+        let cockroach = HashMap::from([
+            (QueryShape::EventByName, "idx_event__name__block"),
+            (
+                QueryShape::EvmLogByContractTopic,
+                "idx_evm_log__contract__topic0__event",
+            ),
+            (
+                QueryShape::EthTransactionByContractSighash,
+                "IDX_frontier_ethereum_transaction__contract__sighash__call",
+            ),
+        ]);
+        IndexHints {
+            cockroach: cockroach
+                .into_iter()
+                .map(|(shape, index)| (shape, index.to_string()))
+                .collect(),
+        }
+    }
+}
+
+impl IndexHints {
+    pub fn from_json(json: &str) -> Result<IndexHints, Error> {
+        let config = serde_json::from_str::<Config>(json)?;
+        if !config.postgres.is_empty() {
+            let message =
+                "index hints aren't supported by PostgreSQL, only `cockroach` ones can be set";
+            return Err(Error::Internal(message.to_string()));
+        }
+        let mut hints = IndexHints::default();
+        for (shape, index) in config.cockroach {
+            match index {
+                Some(index) => hints.cockroach.insert(shape, index),
+                None => hints.cockroach.remove(&shape),
+            };
+        }
+        Ok(hints)
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<IndexHints, Error> {
+        let json = std::fs::read_to_string(path)?;
+        IndexHints::from_json(&json)
+    }
+
+    pub fn index(&self, shape: QueryShape, database_type: &DatabaseType) -> Option<&str> {
+        match database_type {
+            DatabaseType::Postgres => None,
+            DatabaseType::Cockroach => self.cockroach.get(&shape).map(|index| index.as_str()),
+        }
+    }
+
+    /// Table name for a query, followed by an index hint if any.
+    pub fn table(&self, shape: QueryShape, database_type: &DatabaseType) -> String {
+        match self.index(shape, database_type) {
+            Some(index) => format!("{}@{}", shape.table(), index),
+            None => shape.table().to_string(),
+        }
+    }
+}
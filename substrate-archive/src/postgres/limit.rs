@@ -1,9 +1,15 @@
 // NOTE: this module is depricated and exists only for backword compatibility
 
+use super::hints::{IndexHints, QueryShape};
 use super::serializer::{CallSerializer, EventSerializer, EvmLogSerializer, ExtrinsicSerializer};
-use super::utils::unify_and_merge;
+use super::session::Session;
+use super::utils::{
+    blocks_by_ids, calls_by_ids, calls_with_parents_by_ids, events_by_ids,
+    executed_events_by_call_ids, extrinsics_by_ids, unify_and_merge, BLOCK_COLUMNS, CALL_COLUMNS,
+    EVENT_COLUMNS,
+};
 use super::{BatchOptions, BatchResponse, DatabaseType};
-use crate::entities::{Batch, BlockHeader, Call, Event, EvmLog, Extrinsic};
+use crate::entities::{Batch, BlockHeader, Call, Event, EvmLog, Extrinsic, Relations};
 use crate::error::Error;
 use crate::fields::{CallFields, EventFields, EvmLogFields, ExtrinsicFields};
 use crate::metrics::ObserverExt;
@@ -12,14 +18,15 @@ use crate::selection::{
     EthTransactSelection, EventDataSelection, EventSelection, EvmLogSelection,
     GearMessageEnqueuedSelection, GearUserMessageSentSelection,
 };
-use sqlx::postgres::{PgArguments, Postgres};
-use sqlx::{Arguments, Pool};
+use crate::sql::{cast, select, Parameters};
+use sqlx::postgres::PgArguments;
 use std::cmp::min;
 use std::collections::{HashMap, HashSet};
 
 pub struct LimitBatchLoader<'a> {
-    pool: Pool<Postgres>,
+    session: Session,
     database_type: DatabaseType,
+    index_hints: IndexHints,
     limit: i32,
     from_block: i32,
     to_block: Option<i32>,
@@ -35,32 +42,20 @@ pub struct LimitBatchLoader<'a> {
     acala_evm_executed_failed_selections: &'a Vec<AcalaEvmEventSelection>,
 }
 
-const EVENTS_BY_ID_QUERY: &str = "SELECT
-        id,
-        block_id,
-        index_in_block::int8,
-        phase,
-        extrinsic_id,
-        call_id,
-        name,
-        args,
-        pos::int8
-    FROM event
-    WHERE id = ANY($1::char(23)[])";
-
 impl<'a> LimitBatchLoader<'a> {
     pub async fn load(&self) -> Result<BatchResponse, Error> {
         if self.limit < 1 {
             return Ok(BatchResponse {
                 data: vec![],
                 next_block: None,
+                next_pos: None,
             });
         }
 
         let mut calls = self.load_calls().await?;
         let mut events = self.load_events().await?;
         let mut evm_logs = self.load_evm_logs().await?;
-        let mut eth_transactions = self.load_eth_transactions().await?;
+        let (mut eth_transactions, mut eth_executed) = self.load_eth_transactions().await?;
         let mut contracts_events = self.load_contracts_events().await?;
         let mut messages_enqueued = self.load_messages_enqueued().await?;
         let mut messages_sent = self.load_messages_sent().await?;
@@ -117,6 +112,7 @@ impl<'a> LimitBatchLoader<'a> {
             events.retain(|event| ids.contains(&event.block_id));
             evm_logs.retain(|evm_log| ids.contains(&evm_log.block_id));
             eth_transactions.retain(|call| ids.contains(&call.block_id));
+            eth_executed.retain(|event| ids.contains(&event.block_id));
             contracts_events.retain(|event| ids.contains(&event.block_id));
             messages_enqueued.retain(|event| ids.contains(&event.block_id));
             messages_sent.retain(|event| ids.contains(&event.block_id));
@@ -159,7 +155,7 @@ impl<'a> LimitBatchLoader<'a> {
                             );
                         }
                     }
-                    self.visit_parent_call(&call, &selection.data, &call_lookup, &mut call_fields);
+                    self.visit_parent_call(call, &selection.data, &call_lookup, &mut call_fields);
                 }
             }
         }
@@ -182,7 +178,7 @@ impl<'a> LimitBatchLoader<'a> {
                             );
                         }
                     }
-                    self.visit_parent_call(&call, &selection.data, &call_lookup, &mut call_fields);
+                    self.visit_parent_call(call, &selection.data, &call_lookup, &mut call_fields);
                 }
             }
         }
@@ -263,6 +259,14 @@ impl<'a> LimitBatchLoader<'a> {
             }
         }
         events.append(&mut contracts_events);
+        for event in &eth_executed {
+            let f = EventFields::new(true);
+            event_fields
+                .entry(event.id.clone())
+                .and_modify(|fields| fields.merge(&f))
+                .or_insert_with(|| f);
+        }
+        events.append(&mut eth_executed);
 
         for log in &evm_logs {
             for selection in self.evm_log_selections {
@@ -296,7 +300,7 @@ impl<'a> LimitBatchLoader<'a> {
             }
         }
 
-        let extrinsic_ids = extrinsic_fields.keys().cloned().collect();
+        let extrinsic_ids: Vec<String> = extrinsic_fields.keys().cloned().collect();
         let extrinsics = self.load_extrinsics(&extrinsic_ids).await?;
 
         for extrinsic in &extrinsics {
@@ -315,7 +319,7 @@ impl<'a> LimitBatchLoader<'a> {
         }
 
         if !call_fields_to_load.is_empty() {
-            let call_ids = call_fields_to_load.keys().cloned().collect();
+            let call_ids: Vec<String> = call_fields_to_load.keys().cloned().collect();
             let mut additional_calls = self.load_calls_by_ids(&call_ids).await?;
             let mut call_lookup: HashMap<String, &Call> = HashMap::new();
             for call in &additional_calls {
@@ -404,6 +408,7 @@ impl<'a> LimitBatchLoader<'a> {
         Ok(BatchResponse {
             data: batch,
             next_block: None,
+            next_pos: None,
         })
     }
 
@@ -427,74 +432,38 @@ impl<'a> LimitBatchLoader<'a> {
             .map(|to_block| format!("{:010}", to_block + 1));
 
         let build_args = |_last_id: Option<String>, len: usize, limit: i64| {
-            let mut args = PgArguments::default();
-            let mut sql = String::from(
-                "SELECT block_id
-                FROM call
-                WHERE block_id > $1",
-            );
-            let mut args_len = 1;
-            args.add(&from_block);
+            let mut params = Parameters::default();
+            let mut query = select(["block_id"])
+                .from("call")
+                .where_(format!("block_id > {}", params.add(&from_block)));
             if let Some(to_block) = &to_block {
-                args_len += 1;
-                args.add(to_block);
-                sql.push_str(&format!(" AND block_id < ${}", args_len));
+                query = query.where_(format!("block_id < {}", params.add(to_block)));
             }
             if !wildcard {
-                args_len += 1;
-                args.add(&names);
-                sql.push_str(&format!(" AND name = ANY(${})", args_len));
+                query = query.where_(format!("name = ANY({})", params.add(&names)));
             }
-            sql.push_str(" ORDER BY block_id");
-            args_len += 1;
-            args.add(len as i64);
-            sql.push_str(&format!(" OFFSET ${}", args_len));
-            args_len += 1;
-            args.add(limit);
-            sql.push_str(&format!(" LIMIT ${}", args_len));
-
-            (sql, args)
+            query = query
+                .order_by("block_id")
+                .offset(params.add(len as i64))
+                .limit(params.add(limit));
+            (query.to_string(), params.get())
         };
         let chunk_limit = 5000;
         let ids = self.load_ids(build_args, "call", chunk_limit).await?;
 
-        let mut query = String::from(
-            "SELECT
-                id,
-                parent_id,
-                block_id,
-                extrinsic_id,
-                name,
-                args,
-                success,
-                error,
-                origin,
-                pos::int8
-            FROM call WHERE block_id = ANY($1::char(16)[])",
-        );
-        let mut args = PgArguments::default();
-        args.add(&ids);
+        let mut params = Parameters::default();
+        let mut query = select(CALL_COLUMNS).from("call").where_(format!(
+            "block_id = ANY({})",
+            cast(params.add(&ids), "char(16)[]")
+        ));
         if !wildcard {
-            query.push_str(&format!(" AND name = ANY($2)"));
-            args.add(&names)
+            query = query.where_(format!("name = ANY({})", params.add(&names)));
         }
-        let mut calls = sqlx::query_as_with::<_, Call, _>(&query, args)
-            .fetch_all(&self.pool)
+        let mut calls = sqlx::query_as_with::<_, Call, _>(&query.to_string(), params.get())
+            .fetch_all(&mut *self.session.acquire().await?)
             .observe_duration("call")
             .await?;
 
-        let query = "SELECT
-                id,
-                parent_id,
-                block_id,
-                extrinsic_id,
-                name,
-                args,
-                success,
-                error,
-                origin,
-                pos::int8
-            FROM call WHERE id = ANY($1::varchar(30)[])";
         let mut parents_ids: Vec<String> = calls
             .iter()
             .filter_map(|call| call.parent_id.clone())
@@ -514,9 +483,9 @@ impl<'a> LimitBatchLoader<'a> {
                 })
                 .collect();
             if !to_load.is_empty() {
-                let mut parents = sqlx::query_as::<_, Call>(query)
-                    .bind(to_load)
-                    .fetch_all(&self.pool)
+                let (query, args) = calls_by_ids(&to_load, None);
+                let mut parents = sqlx::query_as_with::<_, Call, _>(&query, args)
+                    .fetch_all(&mut *self.session.acquire().await?)
                     .observe_duration("call")
                     .await?;
                 calls.append(&mut parents);
@@ -534,37 +503,10 @@ impl<'a> LimitBatchLoader<'a> {
         Ok(calls)
     }
 
-    async fn load_calls_by_ids(&self, ids: &Vec<String>) -> Result<Vec<Call>, Error> {
-        // i inject ids into the query otherwise it executes so long
-        let ids = ids
-            .iter()
-            .map(|id| format!("'{}'", id))
-            .collect::<Vec<String>>()
-            .join(", ");
-        let query = format!(
-            "WITH RECURSIVE recursive_call AS (
-                SELECT * FROM call WHERE id IN ({})
-                UNION ALL
-                SELECT DISTINCT ON (call.id) call.*
-                FROM call JOIN recursive_call ON recursive_call.parent_id = call.id
-            )
-            SELECT
-                id,
-                parent_id,
-                block_id,
-                extrinsic_id,
-                name,
-                args,
-                success,
-                error,
-                origin,
-                pos::int8
-            FROM recursive_call
-            ORDER BY block_id",
-            ids
-        );
-        let calls = sqlx::query_as::<_, Call>(&query)
-            .fetch_all(&self.pool)
+    async fn load_calls_by_ids(&self, ids: &[String]) -> Result<Vec<Call>, Error> {
+        let (query, args) = calls_with_parents_by_ids(ids);
+        let calls = sqlx::query_as_with::<_, Call, _>(&query, args)
+            .fetch_all(&mut *self.session.acquire().await?)
             .observe_duration("call")
             .await?;
         Ok(calls)
@@ -591,9 +533,12 @@ impl<'a> LimitBatchLoader<'a> {
         let head = if let Some(to_block) = self.to_block {
             to_block
         } else {
-            let query = "SELECT height::int4 as head FROM block ORDER BY height DESC LIMIT 1";
-            let head = sqlx::query_scalar::<_, i32>(query)
-                .fetch_optional(&self.pool)
+            let query = select(["height::int4 AS head"])
+                .from("block")
+                .order_by("height DESC")
+                .limit("1");
+            let head = sqlx::query_scalar::<_, i32>(&query.to_string())
+                .fetch_optional(&mut *self.session.acquire().await?)
                 .observe_duration("block")
                 .await?;
             if let Some(head) = head {
@@ -608,29 +553,22 @@ impl<'a> LimitBatchLoader<'a> {
             let to_block = min(from_block + range_width, head);
             let block_lt = format!("{:010}", to_block + 1);
 
-            let table = match self.database_type {
-                DatabaseType::Cockroach => "event@idx_event__name__block",
-                DatabaseType::Postgres => "event",
-            };
-            let mut args = PgArguments::default();
-            let mut sql = format!(
-                "SELECT block_id
-                FROM {}
-                WHERE block_id > $1 AND block_id < $2",
-                table
-            );
-            let mut args_len = 2;
-            args.add(&block_gt);
-            args.add(&block_lt);
+            let table = self
+                .index_hints
+                .table(QueryShape::EventByName, &self.database_type);
+            let mut params = Parameters::default();
+            let mut query = select(["block_id"])
+                .from(table)
+                .where_(format!("block_id > {}", params.add(&block_gt)))
+                .where_(format!("block_id < {}", params.add(&block_lt)));
             if !wildcard {
-                args_len += 1;
-                args.add(&names);
-                sql.push_str(&format!(" AND name = ANY(${})", args_len));
+                query = query.where_(format!("name = ANY({})", params.add(&names)));
             }
-            sql.push_str(" ORDER BY block_id");
+            query = query.order_by("block_id");
 
-            let mut blocks = sqlx::query_scalar_with::<_, String, _>(&sql, args)
-                .fetch_all(&self.pool)
+            let sql = query.to_string();
+            let mut blocks = sqlx::query_scalar_with::<_, String, _>(&sql, params.get())
+                .fetch_all(&mut *self.session.acquire().await?)
                 .observe_duration("event")
                 .await?;
             blocks.dedup();
@@ -657,28 +595,16 @@ impl<'a> LimitBatchLoader<'a> {
             from_block = to_block + 1;
         }
 
-        let mut query = String::from(
-            "SELECT
-                id,
-                block_id,
-                index_in_block::int8,
-                phase,
-                extrinsic_id,
-                call_id,
-                name,
-                args,
-                pos::int8
-            FROM event
-            WHERE block_id = ANY($1::char(16)[])",
-        );
-        let mut args = PgArguments::default();
-        args.add(&ids);
+        let mut params = Parameters::default();
+        let mut query = select(EVENT_COLUMNS).from("event").where_(format!(
+            "block_id = ANY({})",
+            cast(params.add(&ids), "char(16)[]")
+        ));
         if !wildcard {
-            query.push_str(&format!(" AND name = ANY($2)"));
-            args.add(&names)
+            query = query.where_(format!("name = ANY({})", params.add(&names)));
         }
-        let events = sqlx::query_as_with::<_, Event, _>(&query, args)
-            .fetch_all(&self.pool)
+        let events = sqlx::query_as_with::<_, Event, _>(&query.to_string(), params.get())
+            .fetch_all(&mut *self.session.acquire().await?)
             .observe_duration("event")
             .await?;
         Ok(events)
@@ -697,7 +623,7 @@ impl<'a> LimitBatchLoader<'a> {
         'outer: loop {
             let (sql, args) = build_args(last_id, ids.len(), limit);
             let result = sqlx::query_scalar_with::<_, String, _>(&sql, args)
-                .fetch_all(&self.pool)
+                .fetch_all(&mut *self.session.acquire().await?)
                 .observe_duration(db_table)
                 .await?;
             if result.is_empty() {
@@ -732,39 +658,26 @@ impl<'a> LimitBatchLoader<'a> {
             .collect::<Vec<String>>();
 
         let build_args = |last_id: Option<String>, _len: usize, limit: i64| {
-            let mut args = PgArguments::default();
-            let mut sql = String::from(
-                "SELECT event_id
-                FROM gear_message_enqueued
-                WHERE program = ANY($1) AND event_id > $2",
-            );
-            let mut args_len = 2;
-            args.add(&programs);
-            if let Some(last_id) = &last_id {
-                args.add(last_id);
-            } else {
-                args.add(&id_gt);
-            }
+            let mut params = Parameters::default();
+            let id_gt = last_id.as_ref().unwrap_or(&id_gt);
+            let mut query = select(["event_id"])
+                .from("gear_message_enqueued")
+                .where_(format!("program = ANY({})", params.add(&programs)))
+                .where_(format!("event_id > {}", params.add(id_gt)));
             if let Some(id_lt) = &id_lt {
-                args_len += 1;
-                args.add(id_lt);
-                sql.push_str(&format!(" AND event_id < ${}", args_len));
+                query = query.where_(format!("event_id < {}", params.add(id_lt)));
             }
-            sql.push_str(" ORDER BY event_id");
-            args_len += 1;
-            args.add(limit);
-            sql.push_str(&format!(" LIMIT ${}", args_len));
-
-            (sql, args)
+            query = query.order_by("event_id").limit(params.add(limit));
+            (query.to_string(), params.get())
         };
         let chunk_limit = 2000;
         let ids = self
             .load_ids(build_args, "gear_message_enqueued", chunk_limit)
             .await?;
 
-        let events = sqlx::query_as::<_, Event>(EVENTS_BY_ID_QUERY)
-            .bind(&ids)
-            .fetch_all(&self.pool)
+        let (query, args) = events_by_ids(&ids, None);
+        let events = sqlx::query_as_with::<_, Event, _>(&query, args)
+            .fetch_all(&mut *self.session.acquire().await?)
             .observe_duration("event")
             .await?;
         Ok(events)
@@ -785,39 +698,26 @@ impl<'a> LimitBatchLoader<'a> {
             .collect::<Vec<String>>();
 
         let build_args = |last_id: Option<String>, _len: usize, limit: i64| {
-            let mut args = PgArguments::default();
-            let mut sql = String::from(
-                "SELECT event_id
-                FROM gear_user_message_sent
-                WHERE program = ANY($1) AND event_id > $2",
-            );
-            let mut args_len = 2;
-            args.add(&programs);
-            if let Some(last_id) = &last_id {
-                args.add(last_id);
-            } else {
-                args.add(&id_gt);
-            }
+            let mut params = Parameters::default();
+            let id_gt = last_id.as_ref().unwrap_or(&id_gt);
+            let mut query = select(["event_id"])
+                .from("gear_user_message_sent")
+                .where_(format!("program = ANY({})", params.add(&programs)))
+                .where_(format!("event_id > {}", params.add(id_gt)));
             if let Some(id_lt) = &id_lt {
-                args_len += 1;
-                args.add(id_lt);
-                sql.push_str(&format!(" AND event_id < ${}", args_len));
+                query = query.where_(format!("event_id < {}", params.add(id_lt)));
             }
-            sql.push_str(" ORDER BY event_id");
-            args_len += 1;
-            args.add(limit);
-            sql.push_str(&format!(" LIMIT ${}", args_len));
-
-            (sql, args)
+            query = query.order_by("event_id").limit(params.add(limit));
+            (query.to_string(), params.get())
         };
         let chunk_limit = 2000;
         let ids = self
             .load_ids(build_args, "gear_user_message_sent", chunk_limit)
             .await?;
 
-        let events = sqlx::query_as::<_, Event>(EVENTS_BY_ID_QUERY)
-            .bind(&ids)
-            .fetch_all(&self.pool)
+        let (query, args) = events_by_ids(&ids, None);
+        let events = sqlx::query_as_with::<_, Event, _>(&query, args)
+            .fetch_all(&mut *self.session.acquire().await?)
             .observe_duration("event")
             .await?;
         Ok(events)
@@ -848,35 +748,19 @@ impl<'a> LimitBatchLoader<'a> {
             .map(|to_block| format!("{:010}", to_block + 1));
 
         let build_args = |last_id: Option<String>, _len: usize, limit: i64| {
-            let mut args = PgArguments::default();
-            let mut sql = format!(
-                "SELECT event_id
-                FROM {}
-                WHERE event_id > $1",
-                event_table
-            );
-            let mut args_len = 1;
-            if let Some(last_id) = &last_id {
-                args.add(last_id);
-            } else {
-                args.add(&id_gt);
-            }
+            let mut params = Parameters::default();
+            let id_gt = last_id.as_ref().unwrap_or(&id_gt);
+            let mut query = select(["event_id"])
+                .from(event_table)
+                .where_(format!("event_id > {}", params.add(id_gt)));
             if let Some(id_lt) = &id_lt {
-                args_len += 1;
-                args.add(id_lt);
-                sql.push_str(&format!(" AND event_id < ${}", args_len));
+                query = query.where_(format!("event_id < {}", params.add(id_lt)));
             }
             if selection.contract != "*" {
-                args_len += 1;
-                args.add(&selection.contract);
-                sql.push_str(&format!(" AND contract = ${}", args_len));
+                query = query.where_(format!("contract = {}", params.add(&selection.contract)));
             }
-            sql.push_str(" ORDER BY event_id");
-            args_len += 1;
-            args.add(limit);
-            sql.push_str(&format!(" LIMIT ${}", args_len));
-
-            (sql, args)
+            query = query.order_by("event_id").limit(params.add(limit));
+            (query.to_string(), params.get())
         };
         let chunk_limit = 2000;
         self.load_ids(build_args, event_table, chunk_limit).await
@@ -896,49 +780,33 @@ impl<'a> LimitBatchLoader<'a> {
                 .map(|to_block| format!("{:010}", to_block + 1));
 
             let build_args = |last_id: Option<String>, _len: usize, limit: i64| {
-                let mut args = PgArguments::default();
-                let mut sql = format!(
-                    "SELECT id
-                    FROM {}
-                    WHERE id > $1",
-                    log_table
-                );
-                let mut args_len = 1;
-                if let Some(last_id) = &last_id {
-                    args.add(last_id);
-                } else {
-                    args.add(&id_gt);
-                }
+                let mut params = Parameters::default();
+                let id_gt = last_id.as_ref().unwrap_or(&id_gt);
+                let mut query = select(["id"])
+                    .from(log_table)
+                    .where_(format!("id > {}", params.add(id_gt)));
                 if let Some(id_lt) = &id_lt {
-                    args_len += 1;
-                    args.add(id_lt);
-                    sql.push_str(&format!(" AND id < ${}", args_len));
+                    query = query.where_(format!("id < {}", params.add(id_lt)));
                 }
                 if selection.contract != "*" {
-                    args_len += 1;
-                    args.add(&selection.contract);
-                    sql.push_str(&format!(" AND event_contract = ${}", args_len));
+                    query = query.where_(format!(
+                        "event_contract = {}",
+                        params.add(&selection.contract)
+                    ));
                 }
                 if let Some(contract) = &log.contract {
-                    args_len += 1;
-                    args.add(contract);
-                    sql.push_str(&format!(" AND contract = ${}", args_len));
+                    query = query.where_(format!("contract = {}", params.add(contract)));
                 }
                 for idx in 0..=3 {
                     if let Some(topics) = log.filter.get(idx) {
                         if !topics.is_empty() {
-                            args_len += 1;
-                            args.add(topics);
-                            sql.push_str(&format!(" AND topic{} = ANY(${})", idx, args_len));
+                            query =
+                                query.where_(format!("topic{} = ANY({})", idx, params.add(topics)));
                         }
                     }
                 }
-                sql.push_str(" ORDER BY id");
-                args_len += 1;
-                args.add(limit);
-                sql.push_str(&format!(" LIMIT ${}", args_len));
-
-                (sql, args)
+                query = query.order_by("id").limit(params.add(limit));
+                (query.to_string(), params.get())
             };
             let chunk_limit = 2000;
             let mut log_ids = self.load_ids(build_args, log_table, chunk_limit).await?;
@@ -946,14 +814,16 @@ impl<'a> LimitBatchLoader<'a> {
         }
         self.trim_ids(&mut ids);
 
-        let query = "SELECT event_id
-            FROM acala_evm_executed_log
-            WHERE id = ANY($1::char(23)[])";
-        let selection_ids = sqlx::query_scalar::<_, String>(query)
-            .bind(&ids)
-            .fetch_all(&self.pool)
-            .observe_duration("acala_evm_executed_log")
-            .await?;
+        let mut params = Parameters::default();
+        let query = select(["event_id"]).from(log_table).where_(format!(
+            "id = ANY({})",
+            cast(params.add(&ids), "char(23)[]")
+        ));
+        let selection_ids =
+            sqlx::query_scalar_with::<_, String, _>(&query.to_string(), params.get())
+                .fetch_all(&mut *self.session.acquire().await?)
+                .observe_duration(log_table)
+                .await?;
         Ok(selection_ids)
     }
 
@@ -976,9 +846,9 @@ impl<'a> LimitBatchLoader<'a> {
             ids.append(&mut selection_ids);
         }
         self.trim_ids(&mut ids);
-        let events = sqlx::query_as::<_, Event>(EVENTS_BY_ID_QUERY)
-            .bind(&ids)
-            .fetch_all(&self.pool)
+        let (query, args) = events_by_ids(&ids, None);
+        let events = sqlx::query_as_with::<_, Event, _>(&query, args)
+            .fetch_all(&mut *self.session.acquire().await?)
             .observe_duration("event")
             .await?;
         Ok(events)
@@ -999,39 +869,26 @@ impl<'a> LimitBatchLoader<'a> {
             .collect::<Vec<String>>();
 
         let build_args = |last_id: Option<String>, _len: usize, limit: i64| {
-            let mut args = PgArguments::default();
-            let mut sql = String::from(
-                "SELECT event_id
-                FROM contracts_contract_emitted
-                WHERE contract = ANY($1) AND event_id > $2",
-            );
-            let mut args_len = 2;
-            args.add(&contracts);
-            if let Some(last_id) = &last_id {
-                args.add(last_id);
-            } else {
-                args.add(&id_gt);
-            }
+            let mut params = Parameters::default();
+            let id_gt = last_id.as_ref().unwrap_or(&id_gt);
+            let mut query = select(["event_id"])
+                .from("contracts_contract_emitted")
+                .where_(format!("contract = ANY({})", params.add(&contracts)))
+                .where_(format!("event_id > {}", params.add(id_gt)));
             if let Some(id_lt) = &id_lt {
-                args_len += 1;
-                args.add(id_lt);
-                sql.push_str(&format!(" AND event_id < ${}", args_len));
+                query = query.where_(format!("event_id < {}", params.add(id_lt)));
             }
-            sql.push_str(" ORDER BY event_id");
-            args_len += 1;
-            args.add(limit);
-            sql.push_str(&format!(" LIMIT ${}", args_len));
-
-            (sql, args)
+            query = query.order_by("event_id").limit(params.add(limit));
+            (query.to_string(), params.get())
         };
         let chunk_limit = 2000;
         let ids = self
             .load_ids(build_args, "contracts_contract_emitted", chunk_limit)
             .await?;
 
-        let events = sqlx::query_as::<_, Event>(EVENTS_BY_ID_QUERY)
-            .bind(&ids)
-            .fetch_all(&self.pool)
+        let (query, args) = events_by_ids(&ids, None);
+        let events = sqlx::query_as_with::<_, Event, _>(&query, args)
+            .fetch_all(&mut *self.session.acquire().await?)
             .observe_duration("event")
             .await?;
         Ok(events)
@@ -1040,7 +897,7 @@ impl<'a> LimitBatchLoader<'a> {
     fn group_evm_selections(
         &'a self,
         selections: &'a Vec<EvmLogSelection>,
-    ) -> Vec<Vec<&EvmLogSelection>> {
+    ) -> Vec<Vec<&'a EvmLogSelection>> {
         let mut grouped: Vec<Vec<&EvmLogSelection>> = vec![];
         for selection in selections {
             let group = grouped.iter_mut().find(|group| {
@@ -1065,29 +922,20 @@ impl<'a> LimitBatchLoader<'a> {
             return Ok(Vec::new());
         }
         let mut ids = Vec::new();
-        for selections in self.group_evm_selections(&self.evm_log_selections) {
+        for selections in self.group_evm_selections(self.evm_log_selections) {
             let id_gt = format!("{:010}", self.from_block);
             let id_lt = self
                 .to_block
                 .map(|to_block| format!("{:010}", to_block + 1));
 
             let build_args = |last_id: Option<String>, _len: usize, limit: i64| {
-                let mut args = PgArguments::default();
-                let mut sql = String::from(
-                    "SELECT event_id
-                    FROM frontier_evm_log
-                    WHERE event_id > $1",
-                );
-                let mut args_len = 1;
-                if let Some(last_id) = &last_id {
-                    args.add(last_id);
-                } else {
-                    args.add(&id_gt);
-                }
+                let mut params = Parameters::default();
+                let id_gt = last_id.as_ref().unwrap_or(&id_gt);
+                let mut query = select(["event_id"])
+                    .from("frontier_evm_log")
+                    .where_(format!("event_id > {}", params.add(id_gt)));
                 if let Some(id_lt) = &id_lt {
-                    args_len += 1;
-                    args.add(id_lt);
-                    sql.push_str(&format!(" AND event_id < ${}", args_len));
+                    query = query.where_(format!("event_id < {}", params.add(id_lt)));
                 }
                 let wildcard = selections.iter().any(|selection| selection.contract == "*");
                 let contracts: Vec<String> = selections
@@ -1095,28 +943,24 @@ impl<'a> LimitBatchLoader<'a> {
                     .map(|selection| selection.contract.clone())
                     .collect();
                 if !wildcard {
-                    args_len += 1;
-                    args.add(&contracts);
-                    sql.push_str(&format!(" AND contract = ANY(${}::char(42)[])", args_len));
+                    query = query.where_(format!(
+                        "contract = ANY({})",
+                        cast(params.add(&contracts), "char(42)[]")
+                    ));
                 }
                 for index in 0..=3 {
                     if let Some(topics) = selections[0].filter.get(index) {
                         if !topics.is_empty() {
-                            args_len += 1;
-                            args.add(topics);
-                            sql.push_str(&format!(
-                                " AND topic{} = ANY(${}::char(66)[])",
-                                index, args_len
+                            query = query.where_(format!(
+                                "topic{} = ANY({})",
+                                index,
+                                cast(params.add(topics), "char(66)[]")
                             ));
                         }
                     }
                 }
-                sql.push_str(" ORDER BY event_id");
-                args_len += 1;
-                args.add(limit);
-                sql.push_str(&format!(" LIMIT ${}", args_len));
-
-                (sql, args)
+                query = query.order_by("event_id").limit(params.add(limit));
+                (query.to_string(), params.get())
             };
             let chunk_limit = 2000;
             let mut log_ids = self
@@ -1125,36 +969,37 @@ impl<'a> LimitBatchLoader<'a> {
             ids.append(&mut log_ids);
         }
         self.trim_ids(&mut ids);
-        let query = "SELECT
-                event.id,
-                event.block_id,
-                event.index_in_block::int8,
-                event.phase,
-                event.extrinsic_id,
-                event.call_id,
-                event.name,
-                event.args,
-                event.pos::int8,
-                COALESCE(
+        let mut params = Parameters::default();
+        let columns = EVENT_COLUMNS
+            .into_iter()
+            .map(|column| format!("event.{}", column))
+            .chain([String::from(
+                "COALESCE(
                     jsonb_extract_path_text(executed_event.args, '2'),
                     jsonb_extract_path_text(executed_event.args, 'transactionHash')
-                ) AS evm_tx_hash
-            FROM event
-            JOIN event executed_event
-                ON event.extrinsic_id = executed_event.extrinsic_id
-                    AND executed_event.name = 'Ethereum.Executed'
-            WHERE event.id = ANY($1::char(23)[])";
-        let logs = sqlx::query_as::<_, EvmLog>(query)
-            .bind(&ids)
-            .fetch_all(&self.pool)
+                ) AS evm_tx_hash",
+            )]);
+        let query = select(columns)
+            .from("event")
+            .join(
+                "event executed_event",
+                "event.extrinsic_id = executed_event.extrinsic_id
+                    AND executed_event.name = 'Ethereum.Executed'",
+            )
+            .where_(format!(
+                "event.id = ANY({})",
+                cast(params.add(&ids), "char(23)[]")
+            ));
+        let logs = sqlx::query_as_with::<_, EvmLog, _>(&query.to_string(), params.get())
+            .fetch_all(&mut *self.session.acquire().await?)
             .observe_duration("event")
             .await?;
         Ok(logs)
     }
 
-    async fn load_eth_transactions(&self) -> Result<Vec<Call>, Error> {
+    async fn load_eth_transactions(&self) -> Result<(Vec<Call>, Vec<Event>), Error> {
         if self.eth_transact_selections.is_empty() {
-            return Ok(Vec::new());
+            return Ok((Vec::new(), Vec::new()));
         }
         let mut ids = Vec::new();
         for selection in self.eth_transact_selections {
@@ -1164,39 +1009,22 @@ impl<'a> LimitBatchLoader<'a> {
                 .map(|to_block| format!("{:010}", to_block + 1));
 
             let build_args = |last_id: Option<String>, _len: usize, limit: i64| {
-                let mut args = PgArguments::default();
-                let mut sql = String::from(
-                    "SELECT call_id
-                    FROM frontier_ethereum_transaction
-                    WHERE call_id > $1",
-                );
-                let mut args_len = 1;
-                if let Some(last_id) = &last_id {
-                    args.add(last_id);
-                } else {
-                    args.add(&id_gt);
-                }
+                let mut params = Parameters::default();
+                let id_gt = last_id.as_ref().unwrap_or(&id_gt);
+                let mut query = select(["call_id"])
+                    .from("frontier_ethereum_transaction")
+                    .where_(format!("call_id > {}", params.add(id_gt)));
                 if let Some(id_lt) = &id_lt {
-                    args_len += 1;
-                    args.add(id_lt);
-                    sql.push_str(&format!(" AND call_id < ${}", args_len));
+                    query = query.where_(format!("call_id < {}", params.add(id_lt)));
                 }
                 if selection.contract != "*" {
-                    args_len += 1;
-                    args.add(&selection.contract);
-                    sql.push_str(&format!(" AND contract = ${}", args_len));
+                    query = query.where_(format!("contract = {}", params.add(&selection.contract)));
                 }
                 if let Some(sighash) = &selection.sighash {
-                    args_len += 1;
-                    args.add(sighash);
-                    sql.push_str(&format!(" AND sighash = ${}", args_len));
+                    query = query.where_(format!("sighash = {}", params.add(sighash)));
                 }
-                sql.push_str(" ORDER BY call_id");
-                args_len += 1;
-                args.add(limit);
-                sql.push_str(&format!(" LIMIT ${}", args_len));
-
-                (sql, args)
+                query = query.order_by("call_id").limit(params.add(limit));
+                (query.to_string(), params.get())
             };
             let chunk_limit = 2000;
             let mut selection_ids = self
@@ -1205,24 +1033,22 @@ impl<'a> LimitBatchLoader<'a> {
             ids.append(&mut selection_ids);
         }
         self.trim_ids(&mut ids);
-        let query = "SELECT
-                id,
-                parent_id,
-                block_id,
-                extrinsic_id,
-                name,
-                args,
-                success,
-                error,
-                origin,
-                pos::int8
-            FROM call WHERE id = ANY($1::varchar(30)[])";
-        let mut calls = sqlx::query_as::<_, Call>(query)
-            .bind(&ids)
-            .fetch_all(&self.pool)
+        let (query, args) = calls_by_ids(&ids, None);
+        let mut calls = sqlx::query_as_with::<_, Call, _>(&query, args)
+            .fetch_all(&mut *self.session.acquire().await?)
             .observe_duration("call")
             .await?;
 
+        let events = if ids.is_empty() {
+            vec![]
+        } else {
+            let (query, args) = executed_events_by_call_ids(&ids);
+            sqlx::query_as_with::<_, Event, _>(&query, args)
+                .fetch_all(&mut *self.session.acquire().await?)
+                .observe_duration("event")
+                .await?
+        };
+
         let mut parents_ids: Vec<String> = calls
             .iter()
             .filter_map(|call| call.parent_id.clone())
@@ -1242,9 +1068,9 @@ impl<'a> LimitBatchLoader<'a> {
                 })
                 .collect();
             if !to_load.is_empty() {
-                let mut parents = sqlx::query_as::<_, Call>(query)
-                    .bind(to_load)
-                    .fetch_all(&self.pool)
+                let (query, args) = calls_by_ids(&to_load, None);
+                let mut parents = sqlx::query_as_with::<_, Call, _>(&query, args)
+                    .fetch_all(&mut *self.session.acquire().await?)
                     .observe_duration("call")
                     .await?;
                 calls.append(&mut parents);
@@ -1259,73 +1085,41 @@ impl<'a> LimitBatchLoader<'a> {
             parents_ids.sort();
             parents_ids.dedup();
         }
-        Ok(calls)
+        Ok((calls, events))
     }
 
-    async fn load_blocks_by_ids(&self, ids: &Vec<String>) -> Result<Vec<BlockHeader>, Error> {
-        let query = "SELECT
-                id,
-                height::int8,
-                hash,
-                parent_hash,
-                state_root,
-                extrinsics_root,
-                timestamp,
-                spec_id,
-                validator
-            FROM block
-            WHERE id = ANY($1::char(16)[])";
-        let blocks = sqlx::query_as::<_, BlockHeader>(query)
-            .bind(ids)
-            .fetch_all(&self.pool)
+    async fn load_blocks_by_ids(&self, ids: &[String]) -> Result<Vec<BlockHeader>, Error> {
+        let (query, args) = blocks_by_ids(ids);
+        let blocks = sqlx::query_as_with::<_, BlockHeader, _>(&query, args)
+            .fetch_all(&mut *self.session.acquire().await?)
             .observe_duration("block")
             .await?;
         Ok(blocks)
     }
 
     async fn load_blocks(&self) -> Result<Vec<BlockHeader>, Error> {
-        let query = "SELECT
-                id,
-                height::int8,
-                hash,
-                parent_hash,
-                state_root,
-                extrinsics_root,
-                timestamp,
-                spec_id,
-                validator
-            FROM block
-            WHERE height >= $1 AND ($2 IS null OR height <= $2)
-            ORDER BY height
-            LIMIT $3";
-        let blocks = sqlx::query_as::<_, BlockHeader>(&query)
-            .bind(self.from_block)
-            .bind(self.to_block)
-            .bind(self.limit)
-            .fetch_all(&self.pool)
+        let mut params = Parameters::default();
+        let to_block = params.add(self.to_block);
+        let query = select(BLOCK_COLUMNS)
+            .from("block")
+            .where_(format!("height >= {}", params.add(self.from_block)))
+            .where_any([
+                format!("{} IS null", to_block),
+                format!("height <= {}", to_block),
+            ])
+            .order_by("height")
+            .limit(params.add(self.limit));
+        let blocks = sqlx::query_as_with::<_, BlockHeader, _>(&query.to_string(), params.get())
+            .fetch_all(&mut *self.session.acquire().await?)
             .observe_duration("block")
             .await?;
         Ok(blocks)
     }
 
-    async fn load_extrinsics(&self, ids: &Vec<String>) -> Result<Vec<Extrinsic>, Error> {
-        let query = "SELECT
-                id,
-                block_id,
-                index_in_block::int8,
-                version::int8,
-                signature,
-                success,
-                error,
-                call_id,
-                fee,
-                tip,
-                hash,
-                pos::int8
-            FROM extrinsic WHERE id = ANY($1::char(23)[])";
-        let extrinsics = sqlx::query_as::<_, Extrinsic>(query)
-            .bind(ids)
-            .fetch_all(&self.pool)
+    async fn load_extrinsics(&self, ids: &[String]) -> Result<Vec<Extrinsic>, Error> {
+        let (query, args) = extrinsics_by_ids(ids);
+        let extrinsics = sqlx::query_as_with::<_, Extrinsic, _>(&query, args)
+            .fetch_all(&mut *self.session.acquire().await?)
             .observe_duration("extrinsic")
             .await?;
         Ok(extrinsics)
@@ -1342,7 +1136,7 @@ impl<'a> LimitBatchLoader<'a> {
         for (block_id, mut data) in logs_by_block.into_iter() {
             events_by_block
                 .entry(block_id)
-                .or_insert_with(Vec::new)
+                .or_default()
                 .append(&mut data);
         }
         blocks
@@ -1367,6 +1161,7 @@ impl<'a> LimitBatchLoader<'a> {
                     calls: calls_by_block.remove(&block.id).unwrap_or_default(),
                     events: deduplicated_events,
                     header: block,
+                    relations: Relations::default(),
                 }
             })
             .collect()
@@ -1388,7 +1183,7 @@ impl<'a> LimitBatchLoader<'a> {
                     call: CallFields::from_parent(&data.call.parent),
                     extrinsic: ExtrinsicFields::new(false),
                 };
-                self.visit_parent_call(&parent, &parent_fields, call_lookup, call_fields);
+                self.visit_parent_call(parent, &parent_fields, call_lookup, call_fields);
                 if let Some(fields) = call_fields.get_mut(&parent.id) {
                     fields.call.merge(&parent_fields.call);
                     fields.extrinsic.merge(&parent_fields.extrinsic);
@@ -1418,12 +1213,14 @@ impl<'a> LimitBatchLoader<'a> {
 impl BatchOptions {
     pub(super) fn loader(
         &self,
-        pool: Pool<Postgres>,
+        session: Session,
         database_type: DatabaseType,
-    ) -> LimitBatchLoader {
+        index_hints: IndexHints,
+    ) -> LimitBatchLoader<'_> {
         LimitBatchLoader {
-            pool,
+            session,
             database_type,
+            index_hints,
             limit: self.limit.unwrap(),
             from_block: self.from_block,
             to_block: self.to_block,
@@ -1,45 +1,111 @@
 use self::controller::BatchController;
+use self::density::DensityModel;
+use self::hints::IndexHints;
+use self::session::{CancellableExt, Session};
+use self::utils::BLOCK_COLUMNS;
 use crate::archive::{ArchiveService, BatchOptions, BatchResponse};
-use crate::entities::{Metadata, Status};
+use crate::budget::ResponseSizeLimit;
+use crate::entities::{BlockHeader, Metadata, Status, TableStats};
 use crate::error::Error;
 use crate::metrics::ObserverExt;
+use crate::sql::select;
 use sqlx::{Pool, Postgres};
+use std::sync::Arc;
 
 mod batch;
 mod controller;
+mod density;
 mod fields;
+pub mod hints;
 mod limit;
 mod partial;
 mod selection;
-mod serializer;
-mod utils;
+pub(crate) mod serializer;
+mod session;
+pub(crate) mod utils;
+pub mod validation;
+
+// tables whose approximate row counts are reported by the status
+const ITEM_TABLES: [&str; 12] = [
+    "extrinsic",
+    "call",
+    "event",
+    "frontier_evm_log",
+    "frontier_ethereum_transaction",
+    "contracts_contract_emitted",
+    "gear_message_enqueued",
+    "gear_user_message_sent",
+    "acala_evm_executed",
+    "acala_evm_executed_log",
+    "acala_evm_executed_failed",
+    "acala_evm_executed_failed_log",
+];
 
 #[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum DatabaseType {
     Postgres,
     Cockroach,
 }
 
+impl DatabaseType {
+    /// Detects the database engine, returns it along with the reported version.
+    pub async fn detect(pool: &Pool<Postgres>) -> Result<(DatabaseType, String), Error> {
+        let version = sqlx::query_scalar::<_, String>("SELECT version()")
+            .fetch_one(pool)
+            .await?;
+        let database_type = if version.starts_with("CockroachDB") {
+            DatabaseType::Cockroach
+        } else {
+            DatabaseType::Postgres
+        };
+        Ok((database_type, version))
+    }
+}
+
 pub struct PostgresArchive {
     pool: Pool<Postgres>,
     database_type: DatabaseType,
+    index_hints: IndexHints,
     scan_start_value: u16,
     scan_max_value: u32,
     scan_time_limit: u16,
+    max_scan_time_limit: u16,
+    response_size_limit: ResponseSizeLimit,
+    density: Arc<DensityModel>,
+    confirmation_depth: Option<u32>,
 }
 
 #[async_trait::async_trait]
 impl ArchiveService for PostgresArchive {
     async fn batch(&self, options: &BatchOptions) -> Result<BatchResponse, Error> {
-        let controller = BatchController::new(
+        if options.parent_hash.is_some() && options.from_block > 0 {
+            let query = "SELECT parent_hash FROM block WHERE height = $1
+                UNION SELECT hash FROM block WHERE height = $2";
+            let parents = sqlx::query_scalar::<_, String>(query)
+                .bind(options.from_block)
+                .bind(options.from_block - 1)
+                .fetch_all(&self.pool)
+                .observe_duration("block")
+                .await?;
+            options.check_parent_hash(&parents)?;
+        }
+        let session = Session::new(
             self.pool.clone(),
+            self.database_type == DatabaseType::Postgres,
+        );
+        let controller = BatchController::new(
+            session.clone(),
             self.database_type.clone(),
+            self.index_hints.clone(),
             self.scan_start_value,
             self.scan_max_value,
             self.scan_time_limit,
+            self.max_scan_time_limit,
+            self.response_size_limit,
+            self.density.clone(),
         );
-        controller.load(options).await
+        controller.load(options).cancel_on_drop(session).await
     }
 
     async fn metadata(&self) -> Result<Vec<Metadata>, Error> {
@@ -64,13 +130,51 @@ impl ArchiveService for PostgresArchive {
     }
 
     async fn status(&self) -> Result<Status, Error> {
-        let query = "SELECT height::int8 as head FROM block ORDER BY height DESC LIMIT 1";
-        let status = sqlx::query_as::<_, Status>(query)
+        let query = select(BLOCK_COLUMNS)
+            .from("block")
+            .order_by("height DESC")
+            .limit("1");
+        let head = sqlx::query_as::<_, BlockHeader>(&query.to_string())
+            .fetch_optional(&self.pool)
+            .observe_duration("block")
+            .await?;
+
+        let query = "SELECT height::int8 FROM block ORDER BY height LIMIT 1";
+        let lowest_height = sqlx::query_scalar::<_, i64>(query)
             .fetch_optional(&self.pool)
             .observe_duration("block")
             .await?
-            .unwrap_or_else(|| Status { head: -1 });
-        Ok(status)
+            .unwrap_or(-1);
+
+        let query = "SELECT spec_version::int8 FROM metadata ORDER BY block_height DESC LIMIT 1";
+        let spec_version = sqlx::query_scalar::<_, i64>(query)
+            .fetch_optional(&self.pool)
+            .observe_duration("metadata")
+            .await?;
+
+        // planner statistics, `reltuples` is negative if a table hasn't been analyzed yet
+        let query = "SELECT c.relname::text, c.reltuples::int8 FROM pg_class c
+            JOIN pg_namespace n ON n.oid = c.relnamespace
+            WHERE n.nspname = current_schema() AND c.relname = ANY($1)
+            ORDER BY c.relname";
+        let tables = sqlx::query_as::<_, (String, i64)>(query)
+            .bind(&ITEM_TABLES[..])
+            .fetch_all(&self.pool)
+            .observe_duration("pg_class")
+            .await?
+            .into_iter()
+            .map(|(name, rows)| TableStats {
+                name,
+                rows: (rows >= 0).then_some(rows),
+            })
+            .collect();
+
+        Ok(Status {
+            lowest_height,
+            spec_version,
+            tables,
+            ..Status::new(head.as_ref(), self.confirmation_depth)
+        })
     }
 }
 
@@ -85,9 +189,36 @@ impl PostgresArchive {
         PostgresArchive {
             pool,
             database_type,
+            index_hints: IndexHints::default(),
             scan_start_value,
             scan_max_value,
             scan_time_limit,
+            max_scan_time_limit: scan_time_limit,
+            response_size_limit: ResponseSizeLimit::default(),
+            density: Arc::new(DensityModel::default()),
+            confirmation_depth: None,
         }
     }
+
+    pub fn index_hints(mut self, value: IndexHints) -> PostgresArchive {
+        self.index_hints = value;
+        self
+    }
+
+    /// Upper bound of a time limit requested by a client
+    pub fn max_scan_time_limit(mut self, value: u16) -> PostgresArchive {
+        self.max_scan_time_limit = value;
+        self
+    }
+
+    pub fn response_size_limit(mut self, value: ResponseSizeLimit) -> PostgresArchive {
+        self.response_size_limit = value;
+        self
+    }
+
+    /// Number of blocks `finalized_head` of the status lags behind the head
+    pub fn confirmation_depth(mut self, value: Option<u32>) -> PostgresArchive {
+        self.confirmation_depth = value;
+        self
+    }
 }
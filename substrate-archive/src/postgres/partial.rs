@@ -1,27 +1,29 @@
+use super::density::{DensityModel, Key};
 use super::{batch::BatchLoader, BatchResponse};
-use crate::archive::Selections;
-use crate::entities::Batch;
+use crate::archive::{ScanProgress, Selections};
+use crate::budget::size_of_batch;
 use crate::error::Error;
+use crate::metrics::{SCAN_ITERATIONS, SCAN_START_RANGE_WIDTH};
 use std::cmp::{max, min};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tracing::debug;
 
-const AVERAGE_EVENT_SIZE: usize = 250;
-const AVERAGE_CALL_SIZE: usize = 700;
-const AVERAGE_EXTRINSIC_SIZE: usize = 350;
-
 pub struct PartialOptions {
     pub from_block: i32,
     pub to_block: i32,
     pub include_all_blocks: bool,
+    pub response_size_limit: usize,
+    pub time_limit: Duration,
     pub selections: Selections,
+    pub progress: Option<ScanProgress>,
 }
 
 pub struct PartialBatchLoader {
     loader: BatchLoader,
     scan_start_value: u16,
     scan_max_value: u32,
-    scan_time_limit: u16,
+    density: Arc<DensityModel>,
 }
 
 impl PartialBatchLoader {
@@ -29,13 +31,13 @@ impl PartialBatchLoader {
         loader: BatchLoader,
         scan_start_value: u16,
         scan_max_value: u32,
-        scan_time_limit: u16,
+        density: Arc<DensityModel>,
     ) -> PartialBatchLoader {
         PartialBatchLoader {
             loader,
             scan_start_value,
             scan_max_value,
-            scan_time_limit,
+            density,
         }
     }
 
@@ -43,18 +45,34 @@ impl PartialBatchLoader {
         let mut batch = vec![];
 
         let start_time = Instant::now();
-        let timeout = Duration::from_millis(self.scan_time_limit.into());
         let scan_start_value: i32 = self.scan_start_value.into();
         let scan_max_value: i32 = self.scan_max_value.try_into().unwrap();
         let mut size = 0;
 
+        // previous requests with the same selections already found out
+        // how sparse the data is in this region
+        let key = Key::new(
+            &options.selections,
+            options.include_all_blocks,
+            options.from_block,
+        );
+        let mut range_width = match self.density.get(&key) {
+            Some(learned) => min(max(learned, scan_start_value), scan_max_value),
+            None => scan_start_value,
+        };
+        SCAN_START_RANGE_WIDTH.observe(range_width.into());
+
         let mut from_block = options.from_block;
-        let mut range_width = scan_start_value;
         let mut to_block = min(from_block + range_width - 1, options.to_block);
         let mut total_range = 0;
+        let mut iterations = 0;
 
         loop {
+            iterations += 1;
             debug!("scanning from {from_block} to {to_block}");
+            if let Some(progress) = &options.progress {
+                progress.set(from_block);
+            }
             let mut batch_response = self
                 .loader
                 .load(
@@ -69,25 +87,11 @@ impl PartialBatchLoader {
             batch.append(&mut batch_response.data);
 
             if batch_response.last_block != to_block {
-                return Ok(BatchResponse {
-                    data: batch,
-                    next_block: Some(batch_response.last_block + 1),
-                });
-            }
-
-            total_range += range_width;
-
-            if size > 1024 * 1024 {
+                to_block = batch_response.last_block;
                 break;
             }
 
-            if timeout < start_time.elapsed() {
-                break;
-            }
-
-            if to_block == options.to_block {
-                break;
-            }
+            total_range += range_width;
 
             range_width = if len == 0 {
                 min(range_width * 10, scan_max_value)
@@ -102,23 +106,29 @@ impl PartialBatchLoader {
                 )
             };
 
+            if size > options.response_size_limit {
+                break;
+            }
+
+            if options.time_limit < start_time.elapsed() {
+                break;
+            }
+
+            if to_block == options.to_block {
+                break;
+            }
+
             from_block = to_block + 1;
             to_block = min(from_block + range_width - 1, options.to_block);
         }
 
+        SCAN_ITERATIONS.observe(iterations.into());
+        self.density.set(key.at(to_block + 1), range_width);
+
         Ok(BatchResponse {
             data: batch,
             next_block: Some(to_block + 1),
+            next_pos: None,
         })
     }
 }
-
-fn size_of_batch(batch: &Vec<Batch>) -> usize {
-    let mut size = 0;
-    for item in batch {
-        size += item.calls.len() * AVERAGE_CALL_SIZE;
-        size += item.events.len() * AVERAGE_EVENT_SIZE;
-        size += item.extrinsics.len() * AVERAGE_EXTRINSIC_SIZE;
-    }
-    size
-}
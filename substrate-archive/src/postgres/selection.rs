@@ -45,7 +45,7 @@ impl EvmLogSelection {
         true
     }
 
-    fn topics_match(&self, topics: &Vec<String>, log: &EvmLog, index: usize) -> bool {
+    fn topics_match(&self, topics: &[String], log: &EvmLog, index: usize) -> bool {
         if topics.is_empty() {
             return true;
         }
@@ -79,7 +79,7 @@ impl EvmLogSelection {
         None
     }
 
-    fn get_address<'a>(&'a self, args: &'a Value) -> Option<&str> {
+    fn get_address<'a>(&'a self, args: &'a Value) -> Option<&'a str> {
         if let Some(value) = args.get("address") {
             if let Some(address) = value.as_str() {
                 return Some(address);
@@ -127,7 +127,7 @@ impl EthTransactSelection {
         self.contract == WILDCARD || self.contract == address
     }
 
-    fn get_transaction_address<'a>(&'a self, transaction: &'a Value) -> Option<&str> {
+    fn get_transaction_address<'a>(&'a self, transaction: &'a Value) -> Option<&'a str> {
         let action = transaction.get("action").or_else(|| {
             transaction
                 .get("value")
@@ -250,18 +250,19 @@ impl AcalaEvmEventSelection {
                     }
 
                     let topics_match = log.filter.iter().enumerate().all(|(index, topics)| {
-                        topics.iter().any(|topic| {
-                            if let Some(value) = nested_log.get("topics") {
-                                if let Some(topics) = value.as_array() {
-                                    if let Some(value) = topics.get(index) {
-                                        if let Some(log_topic) = value.as_str() {
-                                            return log_topic == topic;
+                        topics.is_empty()
+                            || topics.iter().any(|topic| {
+                                if let Some(value) = nested_log.get("topics") {
+                                    if let Some(topics) = value.as_array() {
+                                        if let Some(value) = topics.get(index) {
+                                            if let Some(log_topic) = value.as_str() {
+                                                return log_topic == topic;
+                                            }
                                         }
                                     }
                                 }
-                            }
-                            false
-                        })
+                                false
+                            })
                     });
                     if topics_match {
                         return true;
@@ -0,0 +1,189 @@
+use crate::error::Error;
+use crate::metrics::{BATCH_CANCELLATIONS, CANCELLED_STATEMENTS};
+use pin_project::{pin_project, pinned_drop};
+use sqlx::pool::PoolConnection;
+use sqlx::postgres::{PgConnection, Postgres};
+use sqlx::{Connection, Pool};
+use std::collections::HashSet;
+use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use tracing::{debug, warn};
+
+/// Connections of a single request.
+///
+/// A connection goes back to the pool once its statement completes, backends
+/// running statements of the request are remembered so that the statements
+/// can be aborted if the request is dropped before completion.
+#[derive(Clone)]
+pub struct Session {
+    pool: Pool<Postgres>,
+    // backend pids aren't tracked for databases without `pg_cancel_backend`
+    tracked: bool,
+    state: Arc<Mutex<SessionState>>,
+}
+
+#[derive(Default)]
+struct SessionState {
+    // backends running statements of the session
+    busy: HashSet<i32>,
+    cancelled: bool,
+}
+
+impl Session {
+    pub fn new(pool: Pool<Postgres>, tracked: bool) -> Session {
+        Session {
+            pool,
+            tracked,
+            state: Arc::new(Mutex::new(SessionState::default())),
+        }
+    }
+
+    pub async fn acquire(&self) -> Result<SessionConnection, Error> {
+        let mut conn = self.pool.acquire().await?;
+        let pid = if self.tracked {
+            let pid = sqlx::query_scalar::<_, i32>("SELECT pg_backend_pid()")
+                .fetch_one(&mut conn)
+                .await?;
+            self.state.lock().unwrap().busy.insert(pid);
+            Some(pid)
+        } else {
+            None
+        };
+        Ok(SessionConnection {
+            conn: Some(conn),
+            pid,
+            state: self.state.clone(),
+        })
+    }
+
+    /// Aborts statements which are still running on the session connections.
+    ///
+    /// A dropped query doesn't release its connection until the statement ends,
+    /// so cancellation requests are sent over a separate connection.
+    fn cancel(&self) {
+        let pids: Vec<i32> = {
+            let mut state = self.state.lock().unwrap();
+            state.cancelled = true;
+            state.busy.iter().copied().collect()
+        };
+        if pids.is_empty() {
+            return;
+        }
+        // the request is dropped along with a runtime which is being shut down
+        let runtime = match tokio::runtime::Handle::try_current() {
+            Ok(runtime) => runtime,
+            Err(..) => {
+                warn!("statements of a dropped request can't be cancelled without a runtime");
+                return;
+            }
+        };
+        let options = self.pool.connect_options().clone();
+        runtime.spawn(async move {
+            let result: Result<(), sqlx::Error> = async {
+                let mut conn = PgConnection::connect_with(&options).await?;
+                for pid in pids {
+                    let cancelled = sqlx::query_scalar::<_, bool>("SELECT pg_cancel_backend($1)")
+                        .bind(pid)
+                        .fetch_one(&mut conn)
+                        .await?;
+                    if cancelled {
+                        CANCELLED_STATEMENTS.inc();
+                        debug!(pid, "cancelled statement of a dropped request");
+                    }
+                }
+                conn.close().await
+            }
+            .await;
+            if let Err(err) = result {
+                warn!(
+                    error = err.to_string().as_str(),
+                    "failed to cancel statements"
+                );
+            }
+        });
+    }
+}
+
+/// Pool connection which is tracked by its session until it's dropped.
+pub struct SessionConnection {
+    conn: Option<PoolConnection<Postgres>>,
+    pid: Option<i32>,
+    state: Arc<Mutex<SessionState>>,
+}
+
+impl Deref for SessionConnection {
+    type Target = PgConnection;
+
+    fn deref(&self) -> &Self::Target {
+        self.conn.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for SessionConnection {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.conn.as_mut().unwrap()
+    }
+}
+
+impl Drop for SessionConnection {
+    fn drop(&mut self) {
+        let conn = self.conn.take().unwrap();
+        let mut state = self.state.lock().unwrap();
+        if let Some(pid) = self.pid {
+            state.busy.remove(&pid);
+        }
+        if state.cancelled {
+            // the statement may be running until the cancellation arrives, so the
+            // connection is closed rather than handed to another request
+            drop(conn.detach());
+        }
+    }
+}
+
+/// Future cancelling statements of its session if dropped before completion.
+#[pin_project(PinnedDrop)]
+pub struct Cancellable<Fut> {
+    #[pin]
+    inner: Fut,
+    session: Session,
+    completed: bool,
+}
+
+impl<Fut: Future> Future for Cancellable<Fut> {
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = self.project();
+        let output = this.inner.poll(cx);
+        if output.is_ready() {
+            *this.completed = true;
+        }
+        output
+    }
+}
+
+#[pinned_drop]
+impl<Fut> PinnedDrop for Cancellable<Fut> {
+    // runs before the inner future is dropped, so its connections are still tracked
+    fn drop(self: Pin<&mut Self>) {
+        if !self.completed {
+            BATCH_CANCELLATIONS.inc();
+            self.session.cancel();
+        }
+    }
+}
+
+pub trait CancellableExt: Sized + Future {
+    fn cancel_on_drop(self, session: Session) -> Cancellable<Self> {
+        Cancellable {
+            inner: self,
+            session,
+            completed: false,
+        }
+    }
+}
+
+impl<F: Future> CancellableExt for F {}
@@ -1,4 +1,7 @@
+use super::batch::Page;
+use crate::sql::{cast, select, Parameters, Select};
 use serde_json::{Map, Value};
+use sqlx::postgres::PgArguments;
 use std::collections::HashMap;
 
 // removes duplicates and merge fields between two entities with the same id
@@ -19,12 +22,11 @@ pub fn unify_and_merge(values: Vec<Value>, fields: Vec<&str>) -> Vec<Value> {
     }
     instances_by_id
         .values()
-        .into_iter()
         .map(|duplicates| {
             let mut object = Map::new();
             for field in &fields {
                 let instance = duplicates
-                    .into_iter()
+                    .iter()
                     .find(|instance| instance.get(field).is_some());
                 if let Some(instance) = instance {
                     object.insert(field.to_string(), instance.get(field).unwrap().clone());
@@ -34,3 +36,167 @@ pub fn unify_and_merge(values: Vec<Value>, fields: Vec<&str>) -> Vec<Value> {
         })
         .collect()
 }
+
+pub const BLOCK_COLUMNS: [&str; 9] = [
+    "id",
+    "height::int8",
+    "hash",
+    "parent_hash",
+    "state_root",
+    "extrinsics_root",
+    "timestamp",
+    "spec_id",
+    "validator",
+];
+
+pub const EXTRINSIC_COLUMNS: [&str; 12] = [
+    "id",
+    "block_id",
+    "index_in_block::int8",
+    "version::int8",
+    "signature",
+    "success",
+    "error",
+    "call_id",
+    "fee",
+    "tip",
+    "hash",
+    "pos::int8",
+];
+
+pub const CALL_COLUMNS: [&str; 10] = [
+    "id",
+    "parent_id",
+    "block_id",
+    "extrinsic_id",
+    "name",
+    "args",
+    "success",
+    "error",
+    "origin",
+    "pos::int8",
+];
+
+pub const EVENT_COLUMNS: [&str; 9] = [
+    "id",
+    "block_id",
+    "index_in_block::int8",
+    "phase",
+    "extrinsic_id",
+    "call_id",
+    "name",
+    "args",
+    "pos::int8",
+];
+
+// explicit casts keep primary key indexes usable
+
+pub fn blocks_by_ids(ids: &[String]) -> (String, PgArguments) {
+    let mut params = Parameters::default();
+    let query = select(BLOCK_COLUMNS)
+        .from("block")
+        .where_(format!("id = ANY({})", cast(params.add(ids), "char(16)[]")));
+    (query.to_string(), params.get())
+}
+
+pub fn extrinsics_by_ids(ids: &[String]) -> (String, PgArguments) {
+    let mut params = Parameters::default();
+    let query = select(EXTRINSIC_COLUMNS)
+        .from("extrinsic")
+        .where_(format!("id = ANY({})", cast(params.add(ids), "char(23)[]")));
+    (query.to_string(), params.get())
+}
+
+/// Restricts a query over items of a single block to the page, if any.
+pub fn paginate(query: Select, params: &mut Parameters, page: Option<&Page>) -> Select {
+    match page {
+        Some(page) => query
+            .where_(format!("pos >= {}", params.add(page.from_pos)))
+            .order_by("pos")
+            .limit(params.add(page.rows)),
+        None => query,
+    }
+}
+
+pub fn calls_by_ids(ids: &[String], page: Option<&Page>) -> (String, PgArguments) {
+    let mut params = Parameters::default();
+    let query = select(CALL_COLUMNS).from("call").where_(format!(
+        "id = ANY({})",
+        cast(params.add(ids), "varchar(30)[]")
+    ));
+    let query = paginate(query, &mut params, page);
+    (query.to_string(), params.get())
+}
+
+/// Calls with the given ids along with all their ancestors.
+pub fn calls_with_parents_by_ids(ids: &[String]) -> (String, PgArguments) {
+    let mut params = Parameters::default();
+    let parents = select(["call.*"])
+        .distinct_on("call.id")
+        .from("call")
+        .join("recursive_call", "recursive_call.parent_id = call.id");
+    let calls = select(["*"])
+        .from("call")
+        .where_(format!(
+            "id = ANY({})",
+            cast(params.add(ids), "varchar(30)[]")
+        ))
+        .union_all(parents);
+    let query = select(CALL_COLUMNS)
+        .with_recursive("recursive_call", calls)
+        .from("recursive_call")
+        .order_by("block_id");
+    (query.to_string(), params.get())
+}
+
+pub fn events_by_ids(ids: &[String], page: Option<&Page>) -> (String, PgArguments) {
+    let mut params = Parameters::default();
+    let query = select(EVENT_COLUMNS)
+        .from("event")
+        .where_(format!("id = ANY({})", cast(params.add(ids), "char(23)[]")));
+    let query = paginate(query, &mut params, page);
+    (query.to_string(), params.get())
+}
+
+/// `Ethereum.Executed` events emitted by the given calls.
+pub fn executed_events_by_call_ids(ids: &[String]) -> (String, PgArguments) {
+    let mut params = Parameters::default();
+    let query = select(EVENT_COLUMNS)
+        .from("event")
+        .where_(format!(
+            "call_id = ANY({})",
+            cast(params.add(ids), "char(30)[]")
+        ))
+        .where_("name = 'Ethereum.Executed'");
+    (query.to_string(), params.get())
+}
+
+/// `EVM.Log` events without transaction hashes which are to be loaded separately.
+pub fn evm_logs_by_ids(ids: &[String], page: Option<&Page>) -> (String, PgArguments) {
+    let mut params = Parameters::default();
+    let columns = EVENT_COLUMNS.into_iter().chain(["'' AS evm_tx_hash"]);
+    let query = select(columns)
+        .from("event")
+        .where_(format!("id = ANY({})", cast(params.add(ids), "char(23)[]")));
+    let query = paginate(query, &mut params, page);
+    (query.to_string(), params.get())
+}
+
+/// Ethereum transaction hashes of the given extrinsics.
+pub fn evm_tx_hashes_by_extrinsic_ids(ids: &[String]) -> (String, PgArguments) {
+    let mut params = Parameters::default();
+    let query = select([
+        "extrinsic_id",
+        "COALESCE(
+            jsonb_extract_path_text(args, '2'),
+            jsonb_extract_path_text(args, 'transactionHash')
+        ) AS evm_tx_hash",
+    ])
+    .from("event")
+    .where_("name = 'Ethereum.Executed'")
+    .where_(format!(
+        "extrinsic_id = ANY({})",
+        cast(params.add(ids), "char(23)[]")
+    ));
+    (query.to_string(), params.get())
+}
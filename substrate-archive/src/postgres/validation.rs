@@ -0,0 +1,113 @@
+use super::hints::{IndexHints, QueryShape};
+use super::DatabaseType;
+use crate::error::Error;
+use sqlx::{Pool, Postgres};
+use std::collections::HashSet;
+use std::fmt;
+
+/// Part of the archive schema required by a gateway feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feature {
+    Substrate,
+    Evm,
+    Contracts,
+    Gear,
+    Acala,
+}
+
+impl Feature {
+    fn tables(&self) -> &'static [&'static str] {
+        match self {
+            Feature::Substrate => &["block", "extrinsic", "call", "event", "metadata"],
+            Feature::Evm => &["frontier_evm_log", "frontier_ethereum_transaction"],
+            Feature::Contracts => &["contracts_contract_emitted"],
+            Feature::Gear => &["gear_message_enqueued", "gear_user_message_sent"],
+            Feature::Acala => &[
+                "acala_evm_executed",
+                "acala_evm_executed_log",
+                "acala_evm_executed_failed",
+                "acala_evm_executed_failed_log",
+            ],
+        }
+    }
+
+    // queries which may reference an index in a table hint
+    fn query_shapes(&self) -> &'static [QueryShape] {
+        match self {
+            Feature::Substrate => &[QueryShape::EventByName],
+            Feature::Evm => &[
+                QueryShape::EvmLogByContractTopic,
+                QueryShape::EthTransactionByContractSighash,
+            ],
+            _ => &[],
+        }
+    }
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Feature::Substrate => "substrate",
+            Feature::Evm => "evm",
+            Feature::Contracts => "contracts",
+            Feature::Gear => "gear",
+            Feature::Acala => "acala",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Tables and indexes present in the current schema of a database.
+pub struct SchemaInspector {
+    database_type: DatabaseType,
+    index_hints: IndexHints,
+    tables: HashSet<String>,
+    indexes: HashSet<String>,
+}
+
+impl SchemaInspector {
+    pub async fn load(
+        pool: &Pool<Postgres>,
+        database_type: DatabaseType,
+    ) -> Result<SchemaInspector, Error> {
+        let query = "SELECT table_name::text
+            FROM information_schema.tables
+            WHERE table_schema = current_schema()";
+        let tables = sqlx::query_scalar::<_, String>(query)
+            .fetch_all(pool)
+            .await?;
+        let query = "SELECT indexname::text
+            FROM pg_indexes
+            WHERE schemaname = current_schema()";
+        let indexes = sqlx::query_scalar::<_, String>(query)
+            .fetch_all(pool)
+            .await?;
+        Ok(SchemaInspector {
+            database_type,
+            index_hints: IndexHints::default(),
+            tables: tables.into_iter().collect(),
+            indexes: indexes.into_iter().collect(),
+        })
+    }
+
+    pub fn index_hints(mut self, value: IndexHints) -> SchemaInspector {
+        self.index_hints = value;
+        self
+    }
+
+    /// Returns descriptions of the objects required by a feature but absent in the database.
+    pub fn missing(&self, feature: Feature) -> Vec<String> {
+        let tables = feature
+            .tables()
+            .iter()
+            .filter(|table| !self.tables.contains(**table))
+            .map(|table| format!("table {}", table));
+        let indexes = feature
+            .query_shapes()
+            .iter()
+            .filter_map(|shape| self.index_hints.index(*shape, &self.database_type))
+            .filter(|index| !self.indexes.contains(*index))
+            .map(|index| format!("index {}", index));
+        tables.chain(indexes).collect()
+    }
+}
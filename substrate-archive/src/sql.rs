@@ -8,13 +8,23 @@ where
     T::Item: Into<String>,
 {
     Select {
+        with: None,
+        distinct_on: None,
         columns: columns.into_iter().map(|c| c.into()).collect(),
         from: String::new(),
+        joins: vec![],
         conditions: vec![],
+        unions: vec![],
         order_by: None,
+        limit: None,
+        offset: None,
     }
 }
 
+pub fn cast(expr: impl fmt::Display, ty: &str) -> String {
+    format!("{}::{}", expr, ty)
+}
+
 #[derive(Default)]
 pub struct Parameters {
     inner: PgArguments,
@@ -37,47 +47,122 @@ impl Parameters {
 }
 
 pub struct Select {
+    with: Option<(String, Box<Select>)>,
+    distinct_on: Option<String>,
     columns: Vec<String>,
     from: String,
+    joins: Vec<String>,
     conditions: Vec<String>,
+    unions: Vec<Select>,
     order_by: Option<String>,
+    limit: Option<String>,
+    offset: Option<String>,
 }
 
 impl Select {
+    /// Defines a recursive common table expression available to the query.
+    pub fn with_recursive(mut self, name: impl Into<String>, query: Select) -> Select {
+        self.with = Some((name.into(), Box::new(query)));
+        self
+    }
+
+    pub fn distinct_on(mut self, expr: impl Into<String>) -> Select {
+        self.distinct_on = Some(expr.into());
+        self
+    }
+
     pub fn from(mut self, table: impl Into<String>) -> Select {
         self.from = table.into();
         self
     }
 
+    pub fn join(mut self, table: impl Into<String>, condition: impl Into<String>) -> Select {
+        self.joins
+            .push(format!("JOIN {} ON {}", table.into(), condition.into()));
+        self
+    }
+
     pub fn where_(mut self, condition: impl Into<String>) -> Select {
         self.conditions.push(condition.into());
         self
     }
 
+    /// Adds a condition which is satisfied if any of the given ones is,
+    /// an empty group is never satisfied.
+    pub fn where_any<T>(mut self, conditions: T) -> Select
+    where
+        T: IntoIterator,
+        T::Item: Into<String>,
+    {
+        let conditions: Vec<String> = conditions.into_iter().map(|c| c.into()).collect();
+        if conditions.is_empty() {
+            self.conditions.push("FALSE".to_string());
+        } else {
+            self.conditions
+                .push(format!("({})", conditions.join(" OR ")));
+        }
+        self
+    }
+
+    /// Appends rows of another query, `ORDER BY`, `LIMIT` and `OFFSET`
+    /// are applied to the whole result.
+    pub fn union_all(mut self, query: Select) -> Select {
+        self.unions.push(query);
+        self
+    }
+
     pub fn order_by(mut self, expr: impl Into<String>) -> Select {
         self.order_by = Some(expr.into());
         self
     }
+
+    pub fn limit(mut self, expr: impl Into<String>) -> Select {
+        self.limit = Some(expr.into());
+        self
+    }
+
+    pub fn offset(mut self, expr: impl Into<String>) -> Select {
+        self.offset = Some(expr.into());
+        self
+    }
 }
 
 impl fmt::Display for Select {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some((name, query)) = &self.with {
+            write!(f, "WITH RECURSIVE {} AS ({}) ", name, query)?;
+        }
         write!(f, "SELECT")?;
+        if let Some(expr) = &self.distinct_on {
+            write!(f, " DISTINCT ON ({})", expr)?;
+        }
         write!(f, " {}", self.columns.join(", "))?;
         write!(f, " FROM {}", &self.from)?;
+        for join in &self.joins {
+            write!(f, " {}", join)?;
+        }
         if !self.conditions.is_empty() {
             write!(f, " WHERE {}", self.conditions.join(" AND "))?;
         }
+        for query in &self.unions {
+            write!(f, " UNION ALL {}", query)?;
+        }
         if let Some(order_by) = &self.order_by {
             write!(f, " ORDER BY {}", order_by)?;
         }
+        if let Some(limit) = &self.limit {
+            write!(f, " LIMIT {}", limit)?;
+        }
+        if let Some(offset) = &self.offset {
+            write!(f, " OFFSET {}", offset)?;
+        }
         Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::sql::{select, Parameters};
+    use crate::sql::{cast, select, Parameters};
 
     #[test]
     fn display_base_query() {
@@ -92,4 +177,92 @@ mod tests {
             "SELECT col1, col2 FROM table WHERE col1 = $1 ORDER BY col1"
         );
     }
+
+    #[test]
+    fn display_limit_and_offset() {
+        let mut params = Parameters::default();
+        let query = select(["id"])
+            .from("block")
+            .where_(format!("height > {}", params.add(1)))
+            .order_by("height")
+            .limit(params.add(10))
+            .offset(params.add(20))
+            .to_string();
+        assert_eq!(
+            query,
+            "SELECT id FROM block WHERE height > $1 ORDER BY height LIMIT $2 OFFSET $3"
+        );
+    }
+
+    #[test]
+    fn display_or_group() {
+        let mut params = Parameters::default();
+        let query = select(["id"])
+            .from("event")
+            .where_(format!("block_id > {}", params.add("0000000001")))
+            .where_any([
+                format!("name = {}", params.add("Balances.Transfer")),
+                format!("name = {}", params.add("Balances.Deposit")),
+            ])
+            .to_string();
+        assert_eq!(
+            query,
+            "SELECT id FROM event WHERE block_id > $1 AND (name = $2 OR name = $3)"
+        );
+    }
+
+    #[test]
+    fn display_empty_or_group() {
+        let query = select(["id"])
+            .from("event")
+            .where_any(Vec::<String>::new())
+            .to_string();
+        assert_eq!(query, "SELECT id FROM event WHERE FALSE");
+    }
+
+    #[test]
+    fn display_join_and_cast() {
+        let mut params = Parameters::default();
+        let query = select(["event.id", "frontier_evm_log.contract"])
+            .from("event")
+            .join("frontier_evm_log", "frontier_evm_log.event_id = event.id")
+            .where_(format!(
+                "event.id = ANY({})",
+                cast(params.add(vec!["0000000001-000001-00000"]), "char(23)[]")
+            ))
+            .to_string();
+        assert_eq!(
+            query,
+            "SELECT event.id, frontier_evm_log.contract FROM event \
+            JOIN frontier_evm_log ON frontier_evm_log.event_id = event.id \
+            WHERE event.id = ANY($1::char(23)[])"
+        );
+    }
+
+    #[test]
+    fn display_recursive_union() {
+        let mut params = Parameters::default();
+        let anchor = select(["*"])
+            .from("call")
+            .where_(format!("id = {}", params.add("0000000001-000001-00000")));
+        let parents = select(["call.*"])
+            .distinct_on("call.id")
+            .from("call")
+            .join("recursive_call", "recursive_call.parent_id = call.id");
+        let query = select(["id"])
+            .with_recursive("recursive_call", anchor.union_all(parents))
+            .from("recursive_call")
+            .order_by("block_id")
+            .to_string();
+        assert_eq!(
+            query,
+            "WITH RECURSIVE recursive_call AS (\
+            SELECT * FROM call WHERE id = $1 \
+            UNION ALL \
+            SELECT DISTINCT ON (call.id) call.* FROM call \
+            JOIN recursive_call ON recursive_call.parent_id = call.id\
+            ) \
+            SELECT id FROM recursive_call ORDER BY block_id"
+        );
+    }
 }
@@ -0,0 +1,167 @@
+use actix_web::rt::spawn;
+use actix_web::rt::time::sleep;
+use common::{launch_gateway, ADMIN_TOKEN};
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use sqlx::postgres::PgPoolOptions;
+use std::env;
+use std::time::Duration;
+
+#[allow(dead_code)]
+mod common;
+
+const URL: &str = "http://0.0.0.0:8000";
+
+async fn queries(client: &reqwest::Client) -> Value {
+    client
+        .get(format!("{}/admin/queries", URL))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap()
+}
+
+fn batch_query() -> Value {
+    json!({
+        "query": r#"{ batch(fromBlock: 0, toBlock: 1000, events: [{name: "Balances.Transfer"}]) { header { height } } }"#
+    })
+}
+
+#[actix_web::test]
+async fn test_admin_requires_token() {
+    launch_gateway();
+    let client = reqwest::Client::new();
+    let url = format!("{}/admin/queries", URL);
+    let response = client.get(&url).send().await.unwrap();
+    assert!(response.status() == StatusCode::UNAUTHORIZED);
+    let response = client.get(&url).bearer_auth("wrong").send().await.unwrap();
+    assert!(response.status() == StatusCode::UNAUTHORIZED);
+    let response = client
+        .get(&url)
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status() == StatusCode::OK);
+    let response = client.get(format!("{}/ready", URL)).send().await.unwrap();
+    assert!(response.status() == StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn test_admin_aborts_query_and_drains() {
+    launch_gateway();
+    let client = reqwest::Client::new();
+
+    // the query waits for the lock until it's aborted
+    let database_url = env::var("TEST_DATABASE_URL").unwrap();
+    let locker = PgPoolOptions::new().connect(&database_url).await.unwrap();
+    let mut tx = locker.begin().await.unwrap();
+    sqlx::query("LOCK TABLE event IN ACCESS EXCLUSIVE MODE")
+        .execute(&mut tx)
+        .await
+        .unwrap();
+
+    // both requests carry the same request id
+    let send = || {
+        let request = client
+            .post(format!("{}/graphql", URL))
+            .header("X-SQUID-PROCESSOR", "admin-test")
+            .header("X-REQUEST-ID", "duplicate")
+            .json(&batch_query())
+            .send();
+        spawn(async move { request.await.unwrap().json::<Value>().await.unwrap() })
+    };
+    let first = send();
+    let second = send();
+
+    let mut running = vec![];
+    for _ in 0..50 {
+        let status = queries(&client).await;
+        running = status["queries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|query| query["processor"] == "admin-test" && query["fromBlock"] == 0)
+            .cloned()
+            .collect();
+        if running.len() == 2 {
+            break;
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+    assert!(running.len() == 2);
+    let query = &running[0];
+    assert!(query["requestId"] == "duplicate");
+    assert!(query["toBlock"] == 1000);
+    assert!(query["selections"] == json!({"events": ["Balances.Transfer"]}));
+    assert!(running[0]["id"] != running[1]["id"]);
+
+    let response = client
+        .delete(format!("{}/admin/queries/{}", URL, query["id"]))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status() == StatusCode::NO_CONTENT);
+    // the other request with the same request id is still listed
+    let mut ids = vec![];
+    for _ in 0..50 {
+        let status = queries(&client).await;
+        ids = status["queries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|query| query["id"].clone())
+            .collect();
+        if !ids.contains(&query["id"]) {
+            break;
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+    assert!(!ids.contains(&query["id"]));
+    assert!(ids.contains(&running[1]["id"]));
+
+    tx.rollback().await.unwrap();
+    let bodies = [first.await.unwrap(), second.await.unwrap()];
+    let aborted = bodies
+        .iter()
+        .filter(|body| body["errors"][0]["message"] == "query aborted")
+        .count();
+    assert!(aborted == 1);
+
+    let response = client
+        .post(format!("{}/admin/drain", URL))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status() == StatusCode::OK);
+    let response = client
+        .post(format!("{}/graphql", URL))
+        .json(&batch_query())
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status() == StatusCode::SERVICE_UNAVAILABLE);
+    let response = client.get(format!("{}/ready", URL)).send().await.unwrap();
+    assert!(response.status() == StatusCode::SERVICE_UNAVAILABLE);
+
+    client
+        .delete(format!("{}/admin/drain", URL))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    let response = client
+        .post(format!("{}/graphql", URL))
+        .json(&batch_query())
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status() == StatusCode::OK);
+    let response = client.get(format!("{}/ready", URL)).send().await.unwrap();
+    assert!(response.status() == StatusCode::OK);
+}
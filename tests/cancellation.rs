@@ -0,0 +1,115 @@
+use actix_web::rt::time::{sleep, timeout};
+use sqlx::postgres::PgPoolOptions;
+use std::env;
+use std::time::Duration;
+use substrate_archive::archive::{ArchiveService, BatchOptions, Selections};
+use substrate_archive::postgres::PostgresArchive;
+use substrate_archive::selection::{EventDataSelection, EventSelection};
+use substrate_gateway::DatabaseType;
+
+fn cancelled_statements() -> u64 {
+    prometheus::gather()
+        .iter()
+        .filter(|family| family.get_name() == "db_cancelled_statements_total")
+        .flat_map(|family| family.get_metric())
+        .map(|metric| metric.get_counter().get_value() as u64)
+        .sum()
+}
+
+#[actix_web::test]
+async fn test_dropped_batch_cancels_running_statement() {
+    let database_url = env::var("TEST_DATABASE_URL").unwrap();
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&database_url)
+        .await
+        .unwrap();
+    let archive = PostgresArchive::new(pool.clone(), DatabaseType::Postgres, 100, 100_000, 5000);
+    let options = BatchOptions {
+        limit: None,
+        from_block: 0,
+        to_block: Some(100),
+        from_pos: None,
+        max_bytes: None,
+        time_limit_ms: None,
+        max_blocks: None,
+        include_all_blocks: false,
+        parent_hash: None,
+        progress: None,
+        selections: Selections {
+            event: vec![EventSelection {
+                name: "Balances.Transfer".to_string(),
+                data: EventDataSelection::new(false),
+            }],
+            ..Default::default()
+        },
+    };
+
+    // a statement reading events waits for the lock until it's cancelled
+    let locker = PgPoolOptions::new().connect(&database_url).await.unwrap();
+    let mut tx = locker.begin().await.unwrap();
+    sqlx::query("LOCK TABLE event IN ACCESS EXCLUSIVE MODE")
+        .execute(&mut tx)
+        .await
+        .unwrap();
+
+    let cancelled = cancelled_statements();
+    let result = timeout(Duration::from_millis(500), archive.batch(&options)).await;
+    assert!(result.is_err());
+    for _ in 0..50 {
+        if cancelled_statements() > cancelled {
+            break;
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+    assert!(cancelled_statements() > cancelled);
+    // the connection of the cancelled statement isn't handed to other requests
+    assert_eq!(pool.size(), 0);
+
+    // the only pool connection is released while the lock is still held
+    let metadata = timeout(Duration::from_secs(2), archive.metadata()).await;
+    assert!(matches!(metadata, Ok(Ok(..))));
+    tx.rollback().await.unwrap();
+    assert!(archive.batch(&options).await.is_ok());
+}
+
+#[actix_web::test]
+async fn test_scan_returns_connections_between_statements() {
+    let database_url = env::var("TEST_DATABASE_URL").unwrap();
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&database_url)
+        .await
+        .unwrap();
+    // every scan iteration covers 10 blocks, so the scan runs statements until its time limit
+    let archive = PostgresArchive::new(pool, DatabaseType::Postgres, 10, 10, 5000);
+    let options = BatchOptions {
+        limit: None,
+        from_block: 0,
+        to_block: None,
+        from_pos: None,
+        max_bytes: None,
+        time_limit_ms: Some(2000),
+        max_blocks: None,
+        include_all_blocks: false,
+        parent_hash: None,
+        progress: None,
+        selections: Selections {
+            event: vec![EventSelection {
+                name: "Balances.Transfer".to_string(),
+                data: EventDataSelection::new(false),
+            }],
+            ..Default::default()
+        },
+    };
+
+    let scan = archive.batch(&options);
+    let other = async {
+        sleep(Duration::from_millis(200)).await;
+        // the only pool connection is shared with the running scan
+        timeout(Duration::from_millis(500), archive.metadata()).await
+    };
+    let (scan, other) = futures_util::join!(scan, other);
+    assert!(scan.is_ok());
+    assert!(matches!(other, Ok(Ok(..))));
+}
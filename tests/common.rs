@@ -1,15 +1,23 @@
 use actix_web::rt::time::sleep;
 use actix_web::rt::{spawn, Runtime};
+use reqwest::{Certificate, Identity};
 use serde::Deserialize;
 use serde_json::Value;
 use sqlx::postgres::PgPoolOptions;
+use std::path::{Path, PathBuf};
 use std::sync::Once;
 use std::time::Duration;
-use std::{env, thread};
+use std::{env, fs, thread};
 use substrate_gateway::{DatabaseType, SubstrateGateway};
 
 static INIT: Once = Once::new();
 
+#[allow(dead_code)]
+pub const ADMIN_TOKEN: &str = "admin-token";
+
+#[allow(dead_code)]
+pub const CORS_ORIGIN: &str = "http://explorer.test";
+
 pub fn launch_gateway() {
     INIT.call_once(|| {
         let handle = thread::spawn(|| {
@@ -17,11 +25,13 @@ pub fn launch_gateway() {
                 let database_url = env::var("TEST_DATABASE_URL").unwrap();
                 let pool = PgPoolOptions::new().connect(&database_url).await.unwrap();
                 spawn(async {
-                    SubstrateGateway::new(pool, DatabaseType::Postgres)
+                    SubstrateGateway::with_detected_database_type(pool)
                         .evm_support(true)
                         .contracts_support(true)
                         .gear_support(true)
                         .acala_support(true)
+                        .admin_token(ADMIN_TOKEN.to_string())
+                        .cors_allowed_origins(vec![CORS_ORIGIN.to_string()])
                         .run()
                         .await
                 });
@@ -32,6 +42,53 @@ pub fn launch_gateway() {
     })
 }
 
+#[allow(dead_code)]
+pub fn tls_fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/tls")
+        .join(name)
+}
+
+// The runtime outlives the test so that the SIGHUP listener keeps running
+#[allow(dead_code)]
+pub fn launch_tls_gateway(cert: PathBuf, key: PathBuf, client_cert_required: bool) {
+    thread::spawn(move || {
+        Runtime::new().unwrap().block_on(async move {
+            let database_url = env::var("TEST_DATABASE_URL").unwrap();
+            let pool = PgPoolOptions::new().connect(&database_url).await.unwrap();
+            SubstrateGateway::new(pool, DatabaseType::Postgres)
+                .tls(cert, key)
+                .tls_client_ca(tls_fixture("ca.pem"))
+                .tls_client_cert_required(client_cert_required)
+                .run()
+                .await
+        })
+    });
+    thread::sleep(Duration::from_secs(1));
+}
+
+/// Client trusting the given CA, `identity` adds the client certificate issued by `ca.pem`
+#[allow(dead_code)]
+pub fn tls_client(ca: &str, identity: bool) -> reqwest::Client {
+    let ca = Certificate::from_pem(&fs::read(tls_fixture(ca)).unwrap()).unwrap();
+    let mut builder = reqwest::Client::builder().add_root_certificate(ca);
+    if identity {
+        let cert = fs::read(tls_fixture("client.pem")).unwrap();
+        let key = fs::read(tls_fixture("client-key.pem")).unwrap();
+        builder = builder.identity(Identity::from_pkcs8_pem(&cert, &key).unwrap());
+    }
+    builder.build().unwrap()
+}
+
+#[allow(dead_code)]
+pub async fn is_ready_over_tls(client: &reqwest::Client) -> bool {
+    match client.get("https://localhost:8000/ready").send().await {
+        Ok(response) => response.status().is_success(),
+        Err(..) => false,
+    }
+}
+
+#[allow(dead_code)]
 #[derive(Deserialize)]
 pub struct Call {
     pub id: String,
@@ -44,7 +101,7 @@ pub struct Call {
     pub pos: i32,
 }
 
-#[allow(non_snake_case)]
+#[allow(non_snake_case, dead_code)]
 #[derive(Deserialize)]
 pub struct Event {
     pub id: String,
@@ -65,6 +122,7 @@ pub struct Batch {
     pub events: Vec<Event>,
 }
 
+#[allow(dead_code)]
 #[derive(Deserialize)]
 pub struct BatchData {
     pub data: Vec<Batch>,
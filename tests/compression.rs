@@ -0,0 +1,75 @@
+use common::launch_gateway;
+use serde_json::{json, Value};
+use std::io::Read;
+
+#[allow(dead_code)]
+mod common;
+
+async fn post(query: &str, accept_encoding: &str) -> (Option<String>, Vec<u8>) {
+    let response = reqwest::Client::new()
+        .post("http://0.0.0.0:8000/graphql")
+        .header("Accept-Encoding", accept_encoding)
+        .json(&json!({ "query": query }))
+        .send()
+        .await
+        .unwrap();
+    let encoding = response
+        .headers()
+        .get("Content-Encoding")
+        .map(|value| value.to_str().unwrap().to_string());
+    (encoding, response.bytes().await.unwrap().to_vec())
+}
+
+fn decode(encoding: &str, body: &[u8]) -> Value {
+    let mut data = vec![];
+    match encoding {
+        "gzip" => {
+            flate2::read::GzDecoder::new(body)
+                .read_to_end(&mut data)
+                .unwrap();
+        }
+        "br" => {
+            brotli::Decompressor::new(body, 4096)
+                .read_to_end(&mut data)
+                .unwrap();
+        }
+        "zstd" => data = zstd::stream::decode_all(body).unwrap(),
+        _ => panic!("unexpected encoding {}", encoding),
+    };
+    serde_json::from_slice(&data).unwrap()
+}
+
+const BATCH_QUERY: &str = r#"{ batch(limit: 100, events: [{name: "*"}]) { events } }"#;
+
+#[actix_web::test]
+async fn test_response_compressed_with_negotiated_encoding() {
+    launch_gateway();
+    let (encoding, body) = post(BATCH_QUERY, "identity").await;
+    assert!(encoding.is_none());
+    assert!(serde_json::from_slice::<Value>(&body).is_ok());
+
+    let cases = [
+        ("gzip", "gzip"),
+        ("br", "br"),
+        ("zstd", "zstd"),
+        ("gzip, deflate, br, zstd", "zstd"),
+        ("gzip;q=1.0, br;q=0.5", "gzip"),
+        ("*;q=0.1, zstd;q=0", "br"),
+    ];
+    for (accept_encoding, expected_encoding) in cases {
+        let (encoding, body) = post(BATCH_QUERY, accept_encoding).await;
+        assert!(encoding.as_deref() == Some(expected_encoding));
+        let decoded = decode(expected_encoding, &body);
+        assert!(decoded["data"]["batch"].is_array());
+        assert!(body.len() < decoded.to_string().len());
+    }
+}
+
+#[actix_web::test]
+async fn test_small_response_not_compressed() {
+    launch_gateway();
+    let (encoding, body) = post("{ status { head } }", "gzip, br, zstd").await;
+    assert!(encoding.is_none());
+    let response: Value = serde_json::from_slice(&body).unwrap();
+    assert!(response["data"]["status"]["head"].is_i64());
+}
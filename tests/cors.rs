@@ -0,0 +1,67 @@
+use common::{launch_gateway, CORS_ORIGIN};
+use reqwest::{Client, Method};
+
+#[allow(dead_code)]
+mod common;
+
+async fn preflight(path: &str, origin: &str) -> reqwest::Response {
+    Client::new()
+        .request(Method::OPTIONS, format!("http://0.0.0.0:8000{}", path))
+        .header("Origin", origin)
+        .header("Access-Control-Request-Method", "POST")
+        .header(
+            "Access-Control-Request-Headers",
+            "content-type, x-squid-processor",
+        )
+        .send()
+        .await
+        .unwrap()
+}
+
+fn header(response: &reqwest::Response, name: &str) -> Option<String> {
+    response
+        .headers()
+        .get(name)
+        .map(|value| value.to_str().unwrap().to_string())
+}
+
+#[actix_web::test]
+async fn test_graphql_allows_configured_origin() {
+    launch_gateway();
+    let response = preflight("/graphql", CORS_ORIGIN).await;
+    assert!(response.status().is_success());
+    let origin = header(&response, "Access-Control-Allow-Origin");
+    assert_eq!(origin.as_deref(), Some(CORS_ORIGIN));
+    let headers = header(&response, "Access-Control-Allow-Headers").unwrap();
+    assert!(headers.contains("x-squid-processor"));
+    let max_age = header(&response, "Access-Control-Max-Age");
+    assert_eq!(max_age.as_deref(), Some("3600"));
+
+    let response = Client::new()
+        .post("http://0.0.0.0:8000/graphql")
+        .header("Origin", CORS_ORIGIN)
+        .json(&serde_json::json!({ "query": "{ status { head } }" }))
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    let origin = header(&response, "Access-Control-Allow-Origin");
+    assert_eq!(origin.as_deref(), Some(CORS_ORIGIN));
+}
+
+#[actix_web::test]
+async fn test_other_origins_and_routes_are_not_allowed() {
+    launch_gateway();
+    let response = preflight("/graphql", "http://other.test").await;
+    assert!(header(&response, "Access-Control-Allow-Origin").is_none());
+
+    let response = preflight("/metrics", CORS_ORIGIN).await;
+    assert!(header(&response, "Access-Control-Allow-Origin").is_none());
+    let response = Client::new()
+        .get("http://0.0.0.0:8000/metrics")
+        .header("Origin", CORS_ORIGIN)
+        .send()
+        .await
+        .unwrap();
+    assert!(header(&response, "Access-Control-Allow-Origin").is_none());
+}
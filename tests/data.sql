@@ -35,3 +35,6 @@ INSERT INTO call VALUES ('0001818666-000002-af202', NULL, '0001818666-af202', '0
 INSERT INTO event VALUES ('0001818666-000011-af202', '0001818666-af202', 11, 'ApplyExtrinsic', '0001818666-000002-af202', '0001818666-000002-af202', 'EVM.Executed', '{"contract": "0x0000000000000000000100000000000000000080", "from": "0xffffd2ff9b840f6bd74f80df8e532b4d7886ffff", "logs": [{"address": "0x0000000000000000000100000000000000000080", "data": "0x0000000000000000000000000000000000000000000000000000048c27395000", "topics": ["0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef", "0x000000000000000000000000ffffd2ff9b840f6bd74f80df8e532b4d7886ffff", "0x00000000000000000000000075e480db528101a381ce68544611c169ad7eb342"]}], "usedGas": "76860", "usedStorage": 0}', 15);
 INSERT INTO acala_evm_executed VALUES ('0001818666-000011-af202', '0x0000000000000000000100000000000000000080');
 INSERT INTO acala_evm_executed_log VALUES ('0001818666-000000-af202', '0001818666-000011-af202', '0x0000000000000000000100000000000000000080', '0x0000000000000000000100000000000000000080', '0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef', '0x000000000000000000000000ffffd2ff9b840f6bd74f80df8e532b4d7886ffff', '0x00000000000000000000000075e480db528101a381ce68544611c169ad7eb342', NULL);
+INSERT INTO event VALUES ('0001818666-000012-af202', '0001818666-af202', 12, 'ApplyExtrinsic', '0001818666-000002-af202', '0001818666-000002-af202', 'EVM.ExecutedFailed', '{"contract": "0x0000000000000000000100000000000000000081", "exitReason": {"__kind": "Revert", "value": {"__kind": "Reverted"}}, "from": "0xffffd2ff9b840f6bd74f80df8e532b4d7886ffff", "logs": [{"address": "0x0000000000000000000100000000000000000081", "data": "0x", "topics": ["0x8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925"]}], "output": "0x", "usedGas": "21000", "usedStorage": 0}', 16);
+INSERT INTO acala_evm_executed_failed VALUES ('0001818666-000012-af202', '0x0000000000000000000100000000000000000081');
+INSERT INTO acala_evm_executed_failed_log VALUES ('0001818666-000001-af202', '0001818666-000012-af202', '0x0000000000000000000100000000000000000081', '0x0000000000000000000100000000000000000081', '0x8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925', NULL, NULL, NULL);
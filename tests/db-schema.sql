@@ -115,3 +115,21 @@ CREATE TABLE acala_evm_executed_log (
     topic2 char(66),
     topic3 char(66)
 );
+
+
+CREATE TABLE acala_evm_executed_failed (
+    event_id char(23) primary key references event,
+    contract char(42) not null
+);
+
+
+CREATE TABLE acala_evm_executed_failed_log (
+    id char(23) primary key,
+    event_id char(23) not null references event on delete cascade,
+    event_contract char(42) not null,
+    contract char(42) not null,
+    topic0 char(66),
+    topic1 char(66),
+    topic2 char(66),
+    topic3 char(66)
+);
@@ -0,0 +1,371 @@
+// Runs random selections through both batch strategies of `PostgresArchive`
+// (`limit` set and not set) and checks that they return the same data.
+//
+// `DIFFERENTIAL_SEED` and `DIFFERENTIAL_CASES` env variables allow to reproduce
+// a failed run or to make it longer.
+
+use serde_json::Value;
+use sqlx::postgres::PgPoolOptions;
+use std::env;
+use substrate_archive::archive::{ArchiveService, BatchOptions, Selections};
+use substrate_archive::entities::Batch;
+use substrate_archive::fields::{
+    CallFields, EventFields, EvmLogFields, ExtrinsicFields, ParentCallFields,
+};
+use substrate_archive::postgres::PostgresArchive;
+use substrate_archive::selection::{
+    AcalaEvmEventSelection, AcalaEvmLog, CallDataSelection, CallSelection, ContractsEventSelection,
+    EthTransactSelection, EventDataSelection, EventSelection, EvmLogDataSelection, EvmLogSelection,
+    GearMessageEnqueuedSelection, GearUserMessageSentSelection,
+};
+use substrate_gateway::DatabaseType;
+
+const HEAD: i32 = 1818666;
+
+const EVENT_NAMES: &[&str] = &[
+    "*",
+    "Contracts.ContractEmitted",
+    "EVM.Log",
+    "EVM.Executed",
+    "Ethereum.Executed",
+    "Gear.MessageEnqueued",
+    "Gear.UserMessageSent",
+    "Balances.Transfer",
+];
+const CALL_NAMES: &[&str] = &[
+    "*",
+    "Balances.transfer",
+    "Utility.as_sub",
+    "Contracts.call",
+    "Ethereum.transact",
+    "EVM.eth_call",
+    "Gear.send_message",
+    "System.remark",
+];
+const EVM_CONTRACTS: &[&str] = &[
+    "*",
+    "0xb654611f84a8dc429ba3cb4fda9fad236c505a1a",
+    "0x0000000000000000000100000000000000000080",
+    "0x0000000000000000000000000000000000000000",
+];
+const EVM_TOPICS: &[&str] = &[
+    "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",
+    "0x0000000000000000000000000000000000000000000000000000000000000000",
+    "0x000000000000000000000000ffffd2ff9b840f6bd74f80df8e532b4d7886ffff",
+];
+const SIGHASHES: &[&str] = &["0xd0def521", "0xa9059cbb"];
+const CONTRACTS: &[&str] = &[
+    "0xf98402623dbe32d22b67e0a25136b763615c14fd4201b1aac8832ec52aa64d10",
+    "0x0000000000000000000000000000000000000000000000000000000000000000",
+];
+const GEAR_PROGRAMS: &[&str] = &[
+    "0x5466a0b28225bcc8e33f6bdde7a95f5fcf81bfd94d70ca099d0a8bae230dbaa1",
+    "0x0000000000000000000000000000000000000000000000000000000000000000",
+];
+
+// xorshift64*, good enough to enumerate selections and keeps runs reproducible
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545F4914F6CDD1D)
+    }
+
+    fn bool(&mut self) -> bool {
+        self.next().is_multiple_of(2)
+    }
+
+    fn range(&mut self, from: i32, to: i32) -> i32 {
+        from + (self.next() % (to - from + 1) as u64) as i32
+    }
+
+    fn choose<'a>(&mut self, values: &[&'a str]) -> &'a str {
+        values[(self.next() % values.len() as u64) as usize]
+    }
+
+    fn list<T>(&mut self, max: usize, mut f: impl FnMut(&mut Random) -> T) -> Vec<T> {
+        let len = (self.next() % (max as u64 + 1)) as usize;
+        (0..len).map(|_| f(self)).collect()
+    }
+
+    fn topics(&mut self) -> Vec<Vec<String>> {
+        self.list(2, |random| {
+            random.list(2, |random| random.choose(EVM_TOPICS).to_string())
+        })
+    }
+
+    fn parent_call_fields(&mut self) -> ParentCallFields {
+        if self.bool() {
+            return ParentCallFields::new(self.bool());
+        }
+        ParentCallFields {
+            _all: false,
+            args: self.bool(),
+            error: self.bool(),
+            origin: self.bool(),
+            parent: self.bool(),
+        }
+    }
+
+    fn call_fields(&mut self) -> CallFields {
+        if self.bool() {
+            return CallFields::new(self.bool());
+        }
+        CallFields {
+            _all: false,
+            error: self.bool(),
+            origin: self.bool(),
+            args: self.bool(),
+            parent: self.parent_call_fields(),
+        }
+    }
+
+    fn extrinsic_fields(&mut self) -> ExtrinsicFields {
+        if self.bool() {
+            return ExtrinsicFields::new(self.bool());
+        }
+        ExtrinsicFields {
+            _all: false,
+            index_in_block: self.bool(),
+            version: self.bool(),
+            signature: self.bool(),
+            success: self.bool(),
+            error: self.bool(),
+            hash: self.bool(),
+            call: self.call_fields(),
+            fee: self.bool(),
+            tip: self.bool(),
+        }
+    }
+
+    fn event_data(&mut self) -> EventDataSelection {
+        if self.bool() {
+            return EventDataSelection::new(self.bool());
+        }
+        EventDataSelection {
+            event: EventFields {
+                _all: false,
+                index_in_block: self.bool(),
+                phase: self.bool(),
+                extrinsic: self.extrinsic_fields(),
+                call: self.call_fields(),
+                args: self.bool(),
+            },
+        }
+    }
+
+    fn call_data(&mut self) -> CallDataSelection {
+        if self.bool() {
+            return CallDataSelection::new(self.bool());
+        }
+        CallDataSelection {
+            call: self.call_fields(),
+            extrinsic: self.extrinsic_fields(),
+        }
+    }
+
+    fn evm_log_data(&mut self) -> EvmLogDataSelection {
+        if self.bool() {
+            return EvmLogDataSelection::new(self.bool());
+        }
+        EvmLogDataSelection {
+            event: EvmLogFields {
+                _all: false,
+                index_in_block: self.bool(),
+                phase: self.bool(),
+                extrinsic: self.extrinsic_fields(),
+                call: self.call_fields(),
+                args: self.bool(),
+                evm_tx_hash: self.bool(),
+            },
+        }
+    }
+
+    fn acala_evm_event(&mut self) -> AcalaEvmEventSelection {
+        AcalaEvmEventSelection {
+            contract: self.choose(EVM_CONTRACTS).to_string(),
+            logs: self.list(2, |random| AcalaEvmLog {
+                contract: random
+                    .bool()
+                    .then(|| random.choose(&EVM_CONTRACTS[1..]).to_string()),
+                filter: random.topics(),
+            }),
+            data: self.event_data(),
+        }
+    }
+
+    fn selections(&mut self) -> Selections {
+        Selections {
+            call: self.list(2, |random| CallSelection {
+                name: random.choose(CALL_NAMES).to_string(),
+                data: random.call_data(),
+            }),
+            event: self.list(2, |random| EventSelection {
+                name: random.choose(EVENT_NAMES).to_string(),
+                data: random.event_data(),
+            }),
+            evm_log: self.list(2, |random| EvmLogSelection {
+                contract: random.choose(EVM_CONTRACTS).to_string(),
+                filter: random.topics(),
+                data: random.evm_log_data(),
+            }),
+            eth_transact: self.list(2, |random| EthTransactSelection {
+                contract: random.choose(EVM_CONTRACTS).to_string(),
+                sighash: random.bool().then(|| random.choose(SIGHASHES).to_string()),
+                data: random.call_data(),
+            }),
+            contracts_event: self.list(2, |random| ContractsEventSelection {
+                contract: random.choose(CONTRACTS).to_string(),
+                data: random.event_data(),
+            }),
+            gear_message_enqueued: self.list(1, |random| GearMessageEnqueuedSelection {
+                program: random.choose(GEAR_PROGRAMS).to_string(),
+                data: random.event_data(),
+            }),
+            gear_user_message_sent: self.list(1, |random| GearUserMessageSentSelection {
+                program: random.choose(GEAR_PROGRAMS).to_string(),
+                data: random.event_data(),
+            }),
+            acala_evm_executed: self.list(1, |random| random.acala_evm_event()),
+            acala_evm_executed_failed: self.list(1, |random| random.acala_evm_event()),
+        }
+    }
+}
+
+// height, events, calls, extrinsics
+type NormalizedBlock = (i64, Vec<Value>, Vec<Value>, Vec<Value>);
+
+// items order inside of a block isn't guaranteed by any of the strategies
+fn normalize(data: Vec<Batch>) -> Vec<NormalizedBlock> {
+    let sorted = |mut values: Vec<Value>| {
+        values.sort_by_key(|value| value.get("id").unwrap().as_str().unwrap().to_string());
+        values
+    };
+    let mut blocks: Vec<_> = data
+        .into_iter()
+        .map(|batch| {
+            (
+                batch.header.height,
+                sorted(batch.events),
+                sorted(batch.calls),
+                sorted(batch.extrinsics),
+            )
+        })
+        .collect();
+    blocks.sort_by_key(|block| block.0);
+    blocks
+}
+
+async fn load_with_limit(
+    archive: &PostgresArchive,
+    from_block: i32,
+    to_block: i32,
+    include_all_blocks: bool,
+    selections: &Selections,
+) -> Vec<Batch> {
+    let options = BatchOptions {
+        limit: Some(to_block - from_block + 1),
+        from_block,
+        to_block: Some(to_block),
+        from_pos: None,
+        max_bytes: None,
+        time_limit_ms: None,
+        max_blocks: None,
+        include_all_blocks,
+        parent_hash: None,
+        selections: selections.clone(),
+        progress: None,
+    };
+    archive.batch(&options).await.unwrap().data
+}
+
+async fn load_partially(
+    archive: &PostgresArchive,
+    from_block: i32,
+    to_block: i32,
+    include_all_blocks: bool,
+    selections: &Selections,
+) -> Vec<Batch> {
+    let mut data = vec![];
+    let mut next_block = from_block;
+    while next_block <= to_block {
+        let options = BatchOptions {
+            limit: None,
+            from_block: next_block,
+            to_block: Some(to_block),
+            from_pos: None,
+            max_bytes: None,
+            time_limit_ms: None,
+            max_blocks: None,
+            include_all_blocks,
+            parent_hash: None,
+            selections: selections.clone(),
+            progress: None,
+        };
+        let mut response = archive.batch(&options).await.unwrap();
+        data.append(&mut response.data);
+        next_block = response.next_block.expect("next block expected to be set");
+    }
+    data
+}
+
+#[actix_web::test]
+async fn test_limit_and_partial_loaders_agree() {
+    let seed = env::var("DIFFERENTIAL_SEED")
+        .map(|value| value.parse().unwrap())
+        .unwrap_or(0x5eed);
+    let cases: usize = env::var("DIFFERENTIAL_CASES")
+        .map(|value| value.parse().unwrap())
+        .unwrap_or(100);
+
+    let database_url = env::var("TEST_DATABASE_URL").unwrap();
+    let pool = PgPoolOptions::new().connect(&database_url).await.unwrap();
+    // small scan values make the partial strategy split a range into many requests
+    let archive = PostgresArchive::new(pool, DatabaseType::Postgres, 10, 1_000_000, 5000);
+
+    let mut random = Random(seed);
+    for case in 0..cases {
+        let from_block = if random.bool() {
+            0
+        } else {
+            random.range(0, HEAD)
+        };
+        let to_block = if random.bool() {
+            HEAD + 10
+        } else {
+            random.range(from_block, HEAD + 10)
+        };
+        let include_all_blocks = random.next().is_multiple_of(10) && to_block - from_block < 1000;
+        let selections = random.selections();
+
+        let limit_data = load_with_limit(
+            &archive,
+            from_block,
+            to_block,
+            include_all_blocks,
+            &selections,
+        )
+        .await;
+        let partial_data = load_partially(
+            &archive,
+            from_block,
+            to_block,
+            include_all_blocks,
+            &selections,
+        )
+        .await;
+        assert_eq!(
+            normalize(limit_data),
+            normalize(partial_data),
+            "loaders diverged on case {} (seed {}, blocks {}..={}, include_all_blocks {})",
+            case,
+            seed,
+            from_block,
+            to_block,
+            include_all_blocks,
+        );
+    }
+}
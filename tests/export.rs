@@ -0,0 +1,109 @@
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use sqlx::postgres::PgPoolOptions;
+use std::env;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use substrate_archive::archive::Selections;
+use substrate_archive::postgres::PostgresArchive;
+use substrate_archive::selection::{
+    CallDataSelection, CallSelection, EventDataSelection, EventSelection,
+};
+use substrate_gateway::export::{export, ExportFormat, ExportOptions};
+use substrate_gateway::DatabaseType;
+
+async fn archive() -> PostgresArchive {
+    let database_url = env::var("TEST_DATABASE_URL").unwrap();
+    let pool = PgPoolOptions::new().connect(&database_url).await.unwrap();
+    PostgresArchive::new(pool, DatabaseType::Postgres, 100, 100_000, 5000)
+}
+
+fn options(output: &Path, format: ExportFormat, to_block: i32) -> ExportOptions {
+    ExportOptions {
+        from_block: 0,
+        to_block: Some(to_block),
+        selections: Selections {
+            event: vec![EventSelection {
+                name: "*".to_string(),
+                data: EventDataSelection::new(true),
+            }],
+            call: vec![CallSelection {
+                name: "*".to_string(),
+                data: CallDataSelection::new(true),
+            }],
+            ..Default::default()
+        },
+        output: output.to_path_buf(),
+        format,
+        row_group_size: 1,
+        blocks_per_file: 500,
+    }
+}
+
+fn output_dir() -> PathBuf {
+    env::temp_dir().join(format!("export-{}", uuid::Uuid::new_v4()))
+}
+
+fn csv_rows(path: PathBuf) -> Vec<Vec<String>> {
+    csv::Reader::from_path(path)
+        .unwrap()
+        .records()
+        .map(|record| record.unwrap().iter().map(String::from).collect())
+        .collect()
+}
+
+#[actix_web::test]
+async fn test_export_csv_resumes_from_last_file() {
+    let archive = archive().await;
+    let output = output_dir();
+
+    export(&archive, &options(&output, ExportFormat::Csv, 999))
+        .await
+        .unwrap();
+    let events = csv_rows(output.join("events/0000000000-0000000499.csv"));
+    assert_eq!(events.len(), 2);
+    assert!(events.iter().all(|row| row[0] == "6"));
+    let events = csv_rows(output.join("events/0000000500-0000000999.csv"));
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0][0], "734");
+    assert!(output.join("calls/0000000000-0000000499.csv").exists());
+    assert!(output.join("extrinsics/0000000000-0000000499.csv").exists());
+
+    // files written by the previous run are kept as they are
+    let first = output.join("events/0000000000-0000000499.csv");
+    fs::write(&first, "block_height\n").unwrap();
+    export(&archive, &options(&output, ExportFormat::Csv, 1999))
+        .await
+        .unwrap();
+    assert_eq!(fs::read_to_string(&first).unwrap(), "block_height\n");
+    assert!(output.join("events/0000001500-0000001999.csv").exists());
+    let state = fs::read_to_string(output.join("export.json")).unwrap();
+    let state: serde_json::Value = serde_json::from_str(&state).unwrap();
+    assert_eq!(state["nextBlock"], 2000);
+
+    fs::remove_dir_all(&output).unwrap();
+}
+
+#[actix_web::test]
+async fn test_export_parquet_row_groups() {
+    let archive = archive().await;
+    let output = output_dir();
+
+    export(&archive, &options(&output, ExportFormat::Parquet, 499))
+        .await
+        .unwrap();
+    let file = File::open(output.join("events/0000000000-0000000499.parquet")).unwrap();
+    let reader = SerializedFileReader::new(file).unwrap();
+    let metadata = reader.metadata();
+    assert_eq!(metadata.file_metadata().num_rows(), 2);
+    assert_eq!(metadata.num_row_groups(), 2);
+    let columns: Vec<&str> = metadata
+        .file_metadata()
+        .schema_descr()
+        .columns()
+        .iter()
+        .map(|column| column.name())
+        .collect();
+    assert_eq!(&columns[..3], ["block_height", "id", "index_in_block"]);
+
+    fs::remove_dir_all(&output).unwrap();
+}
@@ -0,0 +1,67 @@
+use common::launch_gateway;
+use serde_json::{json, Value};
+
+#[allow(dead_code)]
+mod common;
+
+async fn post(query: &str, accept: &str) -> (String, Vec<u8>) {
+    let response = reqwest::Client::new()
+        .post("http://0.0.0.0:8000/graphql")
+        .header("Accept", accept)
+        .json(&json!({ "query": query }))
+        .send()
+        .await
+        .unwrap();
+    let content_type = response
+        .headers()
+        .get("Content-Type")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+    (content_type, response.bytes().await.unwrap().to_vec())
+}
+
+const QUERY: &str =
+    "{ batch(fromBlock: 0, toBlock: 10, includeAllBlocks: true) { header { id height hash } } }";
+
+#[actix_web::test]
+async fn test_binary_formats_have_json_structure() {
+    launch_gateway();
+    let (content_type, body) = post(QUERY, "application/json").await;
+    assert!(content_type.starts_with("application/json"));
+    let expected: Value = serde_json::from_slice(&body).unwrap();
+    assert!(!expected["data"]["batch"]["data"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+
+    let (content_type, body) = post(QUERY, "application/msgpack").await;
+    assert!(content_type == "application/msgpack");
+    assert!(rmp_serde::from_slice::<Value>(&body).unwrap() == expected);
+
+    let (content_type, body) = post(QUERY, "application/cbor").await;
+    assert!(content_type == "application/cbor");
+    assert!(ciborium::de::from_reader::<Value, _>(&body[..]).unwrap() == expected);
+}
+
+#[actix_web::test]
+async fn test_json_preferred_unless_binary_format_listed() {
+    launch_gateway();
+    let cases = [
+        ("*/*", "application/json"),
+        (
+            "application/json, application/cbor;q=0.5",
+            "application/json",
+        ),
+        (
+            "application/cbor, application/json;q=0.9",
+            "application/cbor",
+        ),
+        ("application/msgpack;q=0, */*", "application/json"),
+    ];
+    for (accept, expected) in cases {
+        let (content_type, _) = post(QUERY, accept).await;
+        assert!(content_type.starts_with(expected));
+    }
+}
@@ -0,0 +1,150 @@
+use async_graphql::Request;
+use serde_json::Value;
+use substrate_gateway::{Executor, MemoryArchive, SubstrateGateway};
+
+const INTROSPECTION_QUERY: &str = r#"
+query IntrospectionQuery {
+  __schema {
+    queryType { name }
+    mutationType { name }
+    subscriptionType { name }
+    types { ...FullType }
+    directives {
+      name
+      description
+      locations
+      args { ...InputValue }
+    }
+  }
+}
+fragment FullType on __Type {
+  kind
+  name
+  description
+  fields(includeDeprecated: true) {
+    name
+    description
+    args { ...InputValue }
+    type { ...TypeRef }
+    isDeprecated
+    deprecationReason
+  }
+  inputFields { ...InputValue }
+  interfaces { ...TypeRef }
+  enumValues(includeDeprecated: true) {
+    name
+    description
+    isDeprecated
+    deprecationReason
+  }
+  possibleTypes { ...TypeRef }
+}
+fragment InputValue on __InputValue {
+  name
+  description
+  type { ...TypeRef }
+  defaultValue
+}
+fragment TypeRef on __Type {
+  kind
+  name
+  ofType {
+    kind
+    name
+    ofType {
+      kind
+      name
+      ofType {
+        kind
+        name
+        ofType {
+          kind
+          name
+          ofType {
+            kind
+            name
+            ofType {
+              kind
+              name
+              ofType {
+                kind
+                name
+              }
+            }
+          }
+        }
+      }
+    }
+  }
+}
+"#;
+
+fn gateway() -> SubstrateGateway {
+    SubstrateGateway::in_memory(MemoryArchive::default()).evm_support(true)
+}
+
+async fn error_code(executor: &Executor, query: &str) -> Option<String> {
+    let response = executor.execute(Request::new(query)).await;
+    let errors = serde_json::to_value(&response.errors).unwrap();
+    errors[0]["extensions"]["code"].as_str().map(String::from)
+}
+
+fn events(count: usize) -> String {
+    let selections: Vec<String> = (0..count)
+        .map(|index| format!("{{name: \"Pallet.Event{}\"}}", index))
+        .collect();
+    format!(
+        "{{ batch(events: [{}]) {{ header {{ height }} }} }}",
+        selections.join(", ")
+    )
+}
+
+#[actix_web::test]
+async fn test_queries_are_unlimited_by_default() {
+    let executor = gateway().executor().await.unwrap();
+    let response = executor.execute(Request::new(INTROSPECTION_QUERY)).await;
+    assert!(response.errors.is_empty());
+}
+
+#[actix_web::test]
+async fn test_depth_and_complexity_limits() {
+    let gateway = gateway().max_query_depth(5);
+    let executor = gateway.executor().await.unwrap();
+    let code = error_code(&executor, INTROSPECTION_QUERY).await;
+    assert_eq!(code.as_deref(), Some("QUERY_TOO_DEEP"));
+
+    let gateway = gateway.max_query_depth(0).max_query_complexity(10);
+    let executor = gateway.executor().await.unwrap();
+    let code = error_code(&executor, INTROSPECTION_QUERY).await;
+    assert_eq!(code.as_deref(), Some("QUERY_TOO_COMPLEX"));
+    assert_eq!(error_code(&executor, "{ status { head } }").await, None);
+}
+
+#[actix_web::test]
+async fn test_selection_limit() {
+    let executor = gateway().max_selections(3).executor().await.unwrap();
+    assert_eq!(error_code(&executor, &events(3)).await, None);
+    let code = error_code(&executor, &events(4)).await;
+    assert_eq!(code.as_deref(), Some("TOO_MANY_SELECTIONS"));
+}
+
+#[actix_web::test]
+async fn test_topic_limit() {
+    let executor = gateway().max_topics(2).executor().await.unwrap();
+    let topic = format!("\"0x{}\"", "0".repeat(64));
+    let query = |filter: &str| {
+        format!(
+            "{{ batch(evmLogs: [{{contract: \"*\", filter: {}}}]) {{ header {{ height }} }} }}",
+            filter
+        )
+    };
+    let allowed = query(&format!("[[{}, {}]]", topic, topic));
+    assert_eq!(error_code(&executor, &allowed).await, None);
+    let rejected = query(&format!("[[{}], [{}, {}]]", topic, topic, topic));
+    let code = error_code(&executor, &rejected).await;
+    assert_eq!(code.as_deref(), Some("TOO_MANY_TOPICS"));
+
+    let response = executor.execute(Request::new(rejected)).await;
+    let data = serde_json::to_value(&response.data).unwrap();
+    assert_eq!(data, Value::Null);
+}
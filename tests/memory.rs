@@ -0,0 +1,378 @@
+use serde_json::{json, Value};
+use substrate_archive::archive::{ArchiveService, BatchOptions, Selections};
+use substrate_archive::budget::{size_of_batch, ResponseSizeLimit};
+use substrate_archive::entities::Batch;
+use substrate_archive::error::Error;
+use substrate_archive::memory::Fixtures;
+use substrate_archive::selection::{
+    AcalaEvmEventSelection, AcalaEvmLog, CallDataSelection, CallSelection, EthTransactSelection,
+    EventDataSelection, EventSelection, EvmLogDataSelection, EvmLogSelection,
+};
+use substrate_gateway::MemoryArchive;
+
+fn archive() -> MemoryArchive {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/archive.json");
+    MemoryArchive::from_file(path).unwrap()
+}
+
+fn options(selections: Selections) -> BatchOptions {
+    BatchOptions {
+        limit: None,
+        from_block: 0,
+        to_block: None,
+        from_pos: None,
+        max_bytes: None,
+        time_limit_ms: None,
+        max_blocks: None,
+        include_all_blocks: false,
+        parent_hash: None,
+        progress: None,
+        selections,
+    }
+}
+
+fn ids(values: &[Value]) -> Vec<&str> {
+    values
+        .iter()
+        .map(|value| value.get("id").unwrap().as_str().unwrap())
+        .collect()
+}
+
+fn find_block(data: &[Batch], height: i64) -> &Batch {
+    data.iter()
+        .find(|batch| batch.header.height == height)
+        .expect("block expected to be loaded")
+}
+
+#[actix_web::test]
+async fn test_memory_status() {
+    let status = archive().status().await.unwrap();
+    assert!(status.head == 1818666);
+}
+
+#[actix_web::test]
+async fn test_memory_parent_call_loaded() {
+    let selections = Selections {
+        call: vec![CallSelection {
+            name: "Balances.transfer".to_string(),
+            data: CallDataSelection::new(true),
+        }],
+        ..Default::default()
+    };
+    let response = archive().batch(&options(selections)).await.unwrap();
+    assert!(response.data.len() == 1);
+    assert!(response.next_block == Some(1818667));
+    let calls = ids(&response.data[0].calls);
+    assert!(calls.contains(&"0000650677-000003-0f08a-000001"));
+    assert!(calls.contains(&"0000650677-000003-0f08a"));
+}
+
+// the fixture keeps the nested call but drops its parent
+fn orphan_call_fixtures() -> Fixtures {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/archive.json");
+    let mut fixtures: Value =
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    fixtures["calls"]
+        .as_array_mut()
+        .unwrap()
+        .retain(|call| call["id"] != "0000650677-000003-0f08a");
+    serde_json::from_value(fixtures).unwrap()
+}
+
+#[actix_web::test]
+async fn test_memory_missing_parent_call() {
+    let fixtures = orphan_call_fixtures();
+    match fixtures.validate() {
+        Err(Error::Internal(message)) => {
+            assert_eq!(
+                message,
+                "parent call 0000650677-000003-0f08a of call 0000650677-000003-0f08a-000001 is missing"
+            )
+        }
+        _ => panic!("fixtures expected to be rejected"),
+    }
+
+    let selections = Selections {
+        call: vec![CallSelection {
+            name: "Balances.transfer".to_string(),
+            data: CallDataSelection::new(true),
+        }],
+        ..Default::default()
+    };
+    let response = MemoryArchive::new(fixtures)
+        .batch(&options(selections))
+        .await
+        .unwrap();
+    let calls = ids(&response.data[0].calls);
+    assert_eq!(calls, vec!["0000650677-000003-0f08a-000001"]);
+}
+
+#[actix_web::test]
+async fn test_memory_evm_log_has_tx_hash() {
+    let selections = Selections {
+        evm_log: vec![EvmLogSelection {
+            contract: "0xb654611f84a8dc429ba3cb4fda9fad236c505a1a".to_string(),
+            filter: vec![vec![
+                "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef".to_string(),
+            ]],
+            data: EvmLogDataSelection::new(true),
+        }],
+        ..Default::default()
+    };
+    let response = archive().batch(&options(selections)).await.unwrap();
+    let log = &find_block(&response.data, 569006).events[0];
+    let tx_hash = "0x8eafe131eee90e0dfb07d6df46b1aea737834936968da31f807af566a59148b9";
+    assert!(log.get("id").unwrap() == "0000569006-000084-5e412");
+    assert!(log.get("evmTxHash").unwrap() == tx_hash);
+}
+
+#[actix_web::test]
+async fn test_memory_ethereum_transactions() {
+    let selections = Selections {
+        eth_transact: vec![EthTransactSelection {
+            contract: "*".to_string(),
+            sighash: Some("0xd0def521".to_string()),
+            data: CallDataSelection::new(true),
+        }],
+        ..Default::default()
+    };
+    let response = archive().batch(&options(selections)).await.unwrap();
+    let batch = find_block(&response.data, 569006);
+    assert!(ids(&batch.calls) == vec!["0000569006-000018-5e412"]);
+    assert!(ids(&batch.events) == vec!["0000569006-000085-5e412"]);
+}
+
+#[actix_web::test]
+async fn test_memory_acala_evm_executed_logs() {
+    let selections = Selections {
+        acala_evm_executed: vec![AcalaEvmEventSelection {
+            contract: "*".to_string(),
+            logs: vec![AcalaEvmLog {
+                contract: Some("0x0000000000000000000100000000000000000080".to_string()),
+                filter: vec![vec![
+                    "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+                        .to_string(),
+                ]],
+            }],
+            data: EventDataSelection::new(true),
+        }],
+        ..Default::default()
+    };
+    let response = archive().batch(&options(selections)).await.unwrap();
+    let batch = find_block(&response.data, 1818666);
+    assert!(ids(&batch.events) == vec!["0001818666-000011-af202"]);
+}
+
+#[actix_web::test]
+async fn test_memory_limit() {
+    let selections = Selections {
+        event: vec![EventSelection {
+            name: "*".to_string(),
+            data: EventDataSelection::new(false),
+        }],
+        ..Default::default()
+    };
+    let mut options = options(selections);
+    options.limit = Some(2);
+    let response = archive().batch(&options).await.unwrap();
+    let heights: Vec<i64> = response
+        .data
+        .iter()
+        .map(|batch| batch.header.height)
+        .collect();
+    assert!(heights == vec![6, 734]);
+    assert!(response.next_block.is_none());
+}
+
+// block 1 has more events than fit into a single response
+fn huge_block_archive() -> MemoryArchive {
+    let block = |height: i64| {
+        json!({
+            "id": format!("{:010}-0000{}", height, height),
+            "height": height,
+            "hash": format!("0x{:064}", height),
+            "parent_hash": format!("0x{:064}", height - 1),
+            "state_root": "0x",
+            "extrinsics_root": "0x",
+            "timestamp": "2022-01-01T00:00:00Z",
+            "spec_id": "test@1",
+            "validator": null
+        })
+    };
+    let event = |height: i64, index: i64| {
+        json!({
+            "id": format!("{:010}-{:06}-0000{}", height, index, height),
+            "block_id": format!("{:010}-0000{}", height, height),
+            "index_in_block": index,
+            "phase": "Initialization",
+            "extrinsic_id": null,
+            "call_id": null,
+            "name": "System.Remarked",
+            "args": null,
+            "pos": index
+        })
+    };
+    let mut events: Vec<Value> = (0..10_000).map(|index| event(1, index)).collect();
+    events.push(event(2, 0));
+    let fixtures = json!({
+        "blocks": [block(1), block(2)],
+        "events": events
+    });
+    MemoryArchive::from_json(&fixtures.to_string()).unwrap()
+}
+
+fn all_events() -> Selections {
+    Selections {
+        event: vec![EventSelection {
+            name: "*".to_string(),
+            data: EventDataSelection::new(false),
+        }],
+        ..Default::default()
+    }
+}
+
+#[actix_web::test]
+async fn test_memory_huge_block_is_split_with_cursor() {
+    let archive = huge_block_archive();
+    let mut loaded = vec![];
+    let mut pages = 0;
+    let mut cursor = (0, Some(0));
+    while cursor.0 <= 2 {
+        let mut options = options(all_events());
+        options.from_block = cursor.0;
+        options.from_pos = Some(cursor.1.unwrap_or(0));
+        options.max_bytes = Some(100_000);
+        let response = archive.batch(&options).await.unwrap();
+        for batch in &response.data {
+            for event in &batch.events {
+                loaded.push(event.get("id").unwrap().as_str().unwrap().to_string());
+            }
+        }
+        pages += 1;
+        cursor = (response.next_block.unwrap(), response.next_pos);
+    }
+    assert!(pages > 2);
+    assert!(loaded.len() == 10_001);
+    let mut sorted = loaded.clone();
+    sorted.sort();
+    sorted.dedup();
+    assert!(sorted == loaded);
+}
+
+#[actix_web::test]
+async fn test_memory_huge_block_without_cursor() {
+    let response = huge_block_archive()
+        .batch(&options(all_events()))
+        .await
+        .unwrap();
+    assert!(response.data.len() == 2);
+    assert!(response.data[0].events.len() == 10_000);
+    assert!(response.next_block == Some(3));
+    assert!(response.next_pos.is_none());
+}
+
+// the call of the last event of block 1 precedes the other events
+fn related_call_archive() -> MemoryArchive {
+    let event = |index: i64, name: &str, call_id: Option<&str>| {
+        json!({
+            "id": format!("0000000001-{:06}-00001", index),
+            "block_id": "0000000001-00001",
+            "index_in_block": index,
+            "phase": "ApplyExtrinsic",
+            "extrinsic_id": null,
+            "call_id": call_id,
+            "name": name,
+            "args": null,
+            "pos": index + 2
+        })
+    };
+    let mut events: Vec<Value> = (0..1000)
+        .map(|index| event(index, "System.Remarked", None))
+        .collect();
+    events.push(event(
+        1000,
+        "Utility.BatchCompleted",
+        Some("0000000001-000001-00001"),
+    ));
+    let fixtures = json!({
+        "blocks": [{
+            "id": "0000000001-00001",
+            "height": 1,
+            "hash": format!("0x{:064}", 1),
+            "parent_hash": format!("0x{:064}", 0),
+            "state_root": "0x",
+            "extrinsics_root": "0x",
+            "timestamp": "2022-01-01T00:00:00Z",
+            "spec_id": "test@1",
+            "validator": null
+        }],
+        "calls": [{
+            "id": "0000000001-000001-00001",
+            "parent_id": null,
+            "block_id": "0000000001-00001",
+            "extrinsic_id": "0000000001-000001-00001",
+            "name": "Utility.batch",
+            "args": null,
+            "success": true,
+            "error": null,
+            "origin": null,
+            "pos": 1
+        }],
+        "events": events
+    });
+    MemoryArchive::from_json(&fixtures.to_string()).unwrap()
+}
+
+#[actix_web::test]
+async fn test_memory_related_call_is_kept_with_cursor() {
+    let archive = related_call_archive();
+    let selections = || Selections {
+        event: vec![EventSelection {
+            name: "*".to_string(),
+            data: EventDataSelection::new(true),
+        }],
+        ..Default::default()
+    };
+    let mut pages = 0;
+    let mut cursor = (0, Some(0));
+    while cursor.0 <= 1 {
+        let mut options = options(selections());
+        options.from_block = cursor.0;
+        options.from_pos = cursor.1;
+        options.max_bytes = Some(20_000);
+        let response = archive.batch(&options).await.unwrap();
+        for batch in &response.data {
+            let completed = ids(&batch.events).contains(&"0000000001-001000-00001");
+            // the call is returned along with the event referring to it only
+            assert_eq!(ids(&batch.calls).len(), usize::from(completed));
+        }
+        pages += 1;
+        cursor = (response.next_block.unwrap(), response.next_pos);
+    }
+    assert!(pages > 2);
+
+    let response = archive.batch(&options(selections())).await.unwrap();
+    assert_eq!(
+        ids(&response.data[0].calls),
+        vec!["0000000001-000001-00001"]
+    );
+}
+
+#[actix_web::test]
+async fn test_memory_requested_size_limited_by_max() {
+    let archive = huge_block_archive().response_size_limit(ResponseSizeLimit {
+        default: 20_000,
+        max: 50_000,
+    });
+    let mut options = options(all_events());
+    options.from_pos = Some(0);
+    let response = archive.batch(&options).await.unwrap();
+    let size = size_of_batch(&response.data);
+    assert!(size <= 20_000 && size > 19_000);
+
+    options.max_bytes = Some(1_000_000);
+    let response = archive.batch(&options).await.unwrap();
+    let size = size_of_batch(&response.data);
+    assert!(size <= 50_000 && size > 49_000);
+    assert!(response.next_block == Some(1));
+}
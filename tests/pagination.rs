@@ -0,0 +1,107 @@
+use serde_json::Value;
+use sqlx::postgres::PgPoolOptions;
+use std::env;
+use substrate_archive::archive::{ArchiveService, BatchOptions, Selections};
+use substrate_archive::postgres::PostgresArchive;
+use substrate_archive::selection::{
+    CallDataSelection, CallSelection, EventDataSelection, EventSelection,
+};
+use substrate_gateway::DatabaseType;
+
+async fn archive() -> PostgresArchive {
+    let database_url = env::var("TEST_DATABASE_URL").unwrap();
+    let pool = PgPoolOptions::new().connect(&database_url).await.unwrap();
+    PostgresArchive::new(pool, DatabaseType::Postgres, 10, 100_000, 5000)
+}
+
+// block 6 has events at positions 5 and 10 and a call at position 7
+fn options(from_pos: i64, max_bytes: Option<usize>) -> BatchOptions {
+    BatchOptions {
+        limit: None,
+        from_block: 6,
+        to_block: Some(6),
+        from_pos: Some(from_pos),
+        max_bytes,
+        time_limit_ms: None,
+        max_blocks: None,
+        include_all_blocks: false,
+        parent_hash: None,
+        progress: None,
+        selections: Selections {
+            call: vec![CallSelection {
+                name: "*".to_string(),
+                data: CallDataSelection::new(false),
+            }],
+            event: vec![EventSelection {
+                name: "*".to_string(),
+                data: EventDataSelection::new(false),
+            }],
+            ..Default::default()
+        },
+    }
+}
+
+fn positions(items: &[Value]) -> Vec<i64> {
+    items
+        .iter()
+        .map(|item| item.get("pos").unwrap().as_i64().unwrap())
+        .collect()
+}
+
+#[actix_web::test]
+async fn test_resumed_block_starts_at_from_pos() {
+    let response = archive().await.batch(&options(6, None)).await.unwrap();
+    assert_eq!(response.data.len(), 1);
+    assert_eq!(positions(&response.data[0].events), vec![10]);
+    assert_eq!(positions(&response.data[0].calls), vec![7]);
+    assert_eq!(response.next_block, Some(7));
+    assert!(response.next_pos.is_none());
+}
+
+#[actix_web::test]
+async fn test_block_is_loaded_page_by_page() {
+    let archive = archive().await;
+    let mut loaded = vec![];
+    let mut cursor = (6, Some(0));
+    while cursor.0 == 6 {
+        let options = options(cursor.1.unwrap(), Some(1));
+        let response = archive.batch(&options).await.unwrap();
+        for batch in &response.data {
+            let mut page = positions(&batch.calls);
+            page.append(&mut positions(&batch.events));
+            // the budget of a single byte takes a single row
+            assert!(page.len() <= 1);
+            loaded.append(&mut page);
+        }
+        cursor = (response.next_block.unwrap(), response.next_pos);
+    }
+    assert_eq!(loaded, vec![5, 7, 10]);
+    assert_eq!(cursor, (7, None));
+}
+
+#[actix_web::test]
+async fn test_related_call_is_kept_with_its_event() {
+    let archive = archive().await;
+    let mut cursor = (6, Some(0));
+    let mut loaded = vec![];
+    while cursor.0 == 6 {
+        let mut options = options(cursor.1.unwrap(), Some(1));
+        // the event at position 5 refers to the call at position 7
+        options.selections = Selections {
+            event: vec![EventSelection {
+                name: "*".to_string(),
+                data: EventDataSelection::new(true),
+            }],
+            ..Default::default()
+        };
+        let response = archive.batch(&options).await.unwrap();
+        for batch in &response.data {
+            let mut events = positions(&batch.events);
+            let calls = positions(&batch.calls);
+            assert_eq!(events.contains(&5), calls.contains(&7));
+            loaded.append(&mut events);
+        }
+        cursor = (response.next_block.unwrap(), response.next_pos);
+    }
+    assert_eq!(loaded, vec![5, 10]);
+}
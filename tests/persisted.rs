@@ -0,0 +1,131 @@
+use async_graphql::{Request, Value as GraphqlValue};
+use common::launch_gateway;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use substrate_gateway::{Executor, MemoryArchive, PersistedQueries, SubstrateGateway};
+
+#[allow(dead_code)]
+mod common;
+
+const QUERY: &str = "{ status { head } }";
+
+fn sha256(query: &str) -> String {
+    format!("{:x}", Sha256::digest(query.as_bytes()))
+}
+
+fn persisted_query(hash: &str) -> Value {
+    json!({ "persistedQuery": { "version": 1, "sha256Hash": hash } })
+}
+
+async fn post(body: Value) -> Value {
+    reqwest::Client::new()
+        .post("http://0.0.0.0:8000/graphql")
+        .json(&body)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap()
+}
+
+#[actix_web::test]
+async fn test_automatic_persisted_query() {
+    launch_gateway();
+    let query = "{ metadata { id } }";
+    let hash = sha256(query);
+
+    let response = post(json!({ "extensions": persisted_query(&hash) })).await;
+    assert_eq!(response["errors"][0]["message"], "PersistedQueryNotFound");
+
+    let response = post(json!({ "query": query, "extensions": persisted_query(&hash) })).await;
+    assert!(response["errors"].is_null());
+    let expected = response["data"].clone();
+
+    let response = post(json!({ "extensions": persisted_query(&hash) })).await;
+    assert!(response["errors"].is_null());
+    assert_eq!(response["data"], expected);
+
+    let wrong_hash = sha256("{ status { head } }");
+    let response =
+        post(json!({ "query": query, "extensions": persisted_query(&wrong_hash) })).await;
+    assert_eq!(
+        response["errors"][0]["message"],
+        "provided sha does not match query"
+    );
+}
+
+fn request(query: &str, hash: Option<&str>) -> Request {
+    let mut request = Request::new(query);
+    if let Some(hash) = hash {
+        let value = GraphqlValue::from_json(persisted_query(hash)["persistedQuery"].clone());
+        request
+            .extensions
+            .insert("persistedQuery".to_string(), value.unwrap());
+    }
+    request
+}
+
+async fn allow_list_executor() -> Executor {
+    let persisted_queries = json!({ sha256(QUERY): QUERY }).to_string();
+    SubstrateGateway::in_memory(MemoryArchive::default())
+        .persisted_queries(PersistedQueries::from_json(&persisted_queries).unwrap())
+        .persisted_queries_only(true)
+        .executor()
+        .await
+        .unwrap()
+}
+
+#[actix_web::test]
+async fn test_allow_list_accepts_registered_queries() {
+    let executor = allow_list_executor().await;
+    let hash = sha256(QUERY);
+
+    let response = executor.execute(request("", Some(&hash))).await;
+    assert!(response.errors.is_empty());
+    let response = executor.execute(request(QUERY, None)).await;
+    assert!(response.errors.is_empty());
+    let response = executor.execute(request(QUERY, Some(&hash))).await;
+    assert!(response.errors.is_empty());
+}
+
+#[actix_web::test]
+async fn test_allow_list_rejects_unknown_queries() {
+    let executor = allow_list_executor().await;
+    let query = "{ metadata { id } }";
+
+    for request in [
+        request(query, None),
+        request(query, Some(&sha256(query))),
+        request("", Some(&sha256(query))),
+    ] {
+        let response = executor.execute(request).await;
+        assert_eq!(response.errors.len(), 1);
+        let error = serde_json::to_value(&response.errors[0]).unwrap();
+        assert_eq!(error["extensions"]["code"], "PERSISTED_QUERY_NOT_IN_LIST");
+    }
+}
+
+#[actix_web::test]
+async fn test_executor_remembers_automatic_persisted_queries() {
+    let executor = SubstrateGateway::in_memory(MemoryArchive::default())
+        .executor()
+        .await
+        .unwrap();
+    let hash = sha256(QUERY);
+
+    let response = executor.execute(request("", Some(&hash))).await;
+    assert_eq!(response.errors[0].message, "PersistedQueryNotFound");
+    let response = executor.execute(request(QUERY, Some(&hash))).await;
+    assert!(response.errors.is_empty());
+    let response = executor.execute(request("", Some(&hash))).await;
+    assert!(response.errors.is_empty());
+}
+
+#[test]
+fn test_persisted_queries_are_verified() {
+    let persisted_queries = json!({ sha256("{ metadata { id } }"): QUERY }).to_string();
+    assert!(PersistedQueries::from_json(&persisted_queries).is_err());
+    let persisted_queries = json!({ sha256("{ status {"): "{ status {" }).to_string();
+    assert!(PersistedQueries::from_json(&persisted_queries).is_err());
+}
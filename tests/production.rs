@@ -0,0 +1,57 @@
+use async_graphql::Request;
+use common::launch_gateway;
+use substrate_gateway::{MemoryArchive, SubstrateGateway};
+
+#[allow(dead_code)]
+mod common;
+
+const INTROSPECTION: &str = "{ __schema { queryType { name } } }";
+
+#[actix_web::test]
+async fn test_introspection_can_be_disabled() {
+    let gateway = SubstrateGateway::in_memory(MemoryArchive::default());
+    let response = gateway
+        .executor()
+        .await
+        .unwrap()
+        .execute(Request::new(INTROSPECTION))
+        .await;
+    assert!(response.errors.is_empty());
+    let data = response.data.into_json().unwrap();
+    assert_eq!(data["__schema"]["queryType"]["name"], "QueryRoot");
+
+    // production mode only replaces the page served on `/`
+    let gateway = gateway.production();
+    let response = gateway
+        .executor()
+        .await
+        .unwrap()
+        .execute(Request::new(INTROSPECTION))
+        .await;
+    assert!(response.errors.is_empty());
+
+    let executor = gateway
+        .disable_introspection(true)
+        .executor()
+        .await
+        .unwrap();
+    let response = executor.execute(Request::new(INTROSPECTION)).await;
+    let data = response.data.into_json().unwrap();
+    assert!(data["__schema"].is_null());
+    let response = executor.execute(Request::new("{ status { head } }")).await;
+    assert!(response.errors.is_empty());
+}
+
+#[actix_web::test]
+async fn test_playground_is_served_by_default() {
+    launch_gateway();
+    let response = reqwest::get("http://0.0.0.0:8000/").await.unwrap();
+    assert!(response.status().is_success());
+    let content_type = response.headers()["Content-Type"].to_str().unwrap();
+    assert!(content_type.starts_with("text/html"));
+    assert!(response
+        .text()
+        .await
+        .unwrap()
+        .contains("GraphQL Playground"));
+}
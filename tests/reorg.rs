@@ -0,0 +1,127 @@
+use async_graphql::Request;
+use common::launch_gateway;
+use serde_json::{json, Value};
+use substrate_archive::archive::{ArchiveService, BatchOptions, Selections};
+use substrate_archive::error::Error;
+use substrate_gateway::{MemoryArchive, SubstrateGateway};
+
+#[allow(dead_code)]
+mod common;
+
+const HASH_6: &str = "0x7ec9415d3947ac8b0f9e2e843699facc4ee9c3c3e397a07e7b4af7d5006a61a6";
+const PARENT_HASH_6: &str = "0x8a16d35d37c6cf15ea8845506200265534c492463ccbe31ef832fa570692e03f";
+const FORK_HASH: &str = "0x0000000000000000000000000000000000000000000000000000000000000000";
+
+fn archive() -> MemoryArchive {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/archive.json");
+    MemoryArchive::from_file(path).unwrap()
+}
+
+fn options(from_block: i32, parent_hash: &str) -> BatchOptions {
+    BatchOptions {
+        limit: None,
+        from_block,
+        to_block: Some(1000),
+        from_pos: None,
+        max_bytes: None,
+        time_limit_ms: None,
+        max_blocks: None,
+        include_all_blocks: true,
+        parent_hash: Some(parent_hash.to_string()),
+        selections: Selections::default(),
+        progress: None,
+    }
+}
+
+#[actix_web::test]
+async fn test_finalized_head() {
+    let status = archive().status().await.unwrap();
+    assert_eq!(status.finalized_head, status.head);
+
+    let status = archive()
+        .confirmation_depth(Some(10))
+        .status()
+        .await
+        .unwrap();
+    assert_eq!(status.finalized_head, status.head - 10);
+
+    let status = MemoryArchive::default()
+        .confirmation_depth(Some(10))
+        .status()
+        .await
+        .unwrap();
+    assert_eq!((status.head, status.finalized_head), (-1, -1));
+}
+
+#[actix_web::test]
+async fn test_parent_hash_is_checked() {
+    let archive = archive();
+    // parent hash of the block at `fromBlock`
+    let response = archive.batch(&options(6, PARENT_HASH_6)).await.unwrap();
+    assert_eq!(response.data[0].header.height, 6);
+    // hash of the block preceding `fromBlock`
+    assert!(archive.batch(&options(7, HASH_6)).await.is_ok());
+    // nothing to compare with
+    assert!(archive.batch(&options(100, FORK_HASH)).await.is_ok());
+
+    match archive.batch(&options(7, FORK_HASH)).await {
+        Err(Error::Reorg {
+            height,
+            expected,
+            actual,
+        }) => {
+            assert_eq!(height, 7);
+            assert_eq!(expected, FORK_HASH);
+            assert_eq!(actual, HASH_6);
+        }
+        _ => panic!("reorg error expected"),
+    }
+}
+
+#[actix_web::test]
+async fn test_reorg_error_code() {
+    let executor = SubstrateGateway::in_memory(archive())
+        .confirmation_depth(5)
+        .executor()
+        .await
+        .unwrap();
+    let response = executor
+        .execute(Request::new("{ status { head finalizedHead } }"))
+        .await;
+    let data = response.data.into_json().unwrap();
+    assert_eq!(
+        data["status"]["finalizedHead"].as_i64().unwrap(),
+        data["status"]["head"].as_i64().unwrap() - 5
+    );
+
+    let query = format!(
+        r#"{{ batch(fromBlock: 7, parentHash: "{}") {{ header {{ height }} }} }}"#,
+        FORK_HASH
+    );
+    let response = executor.execute(Request::new(query)).await;
+    let extensions = serde_json::to_value(&response.errors[0].extensions).unwrap();
+    assert_eq!(extensions["code"], "REORG");
+    assert_eq!(extensions["height"], 7);
+    assert_eq!(extensions["parentHash"], HASH_6);
+}
+
+#[actix_web::test]
+async fn test_reorg_over_postgres() {
+    launch_gateway();
+    let post = |parent_hash: &str| {
+        let query = format!(
+            r#"{{ batch(fromBlock: 7, toBlock: 10, parentHash: "{}", includeAllBlocks: true) {{ header {{ height }} }} }}"#,
+            parent_hash
+        );
+        reqwest::Client::new()
+            .post("http://0.0.0.0:8000/graphql")
+            .json(&json!({ "query": query }))
+            .send()
+    };
+    let response: Value = post(HASH_6).await.unwrap().json().await.unwrap();
+    assert!(response["errors"].is_null());
+
+    let response: Value = post(FORK_HASH).await.unwrap().json().await.unwrap();
+    assert_eq!(response["errors"][0]["extensions"]["code"], "REORG");
+    assert_eq!(response["errors"][0]["extensions"]["parentHash"], HASH_6);
+}
@@ -0,0 +1,148 @@
+use sqlx::postgres::PgPoolOptions;
+use std::env;
+use substrate_archive::archive::{ArchiveService, BatchOptions, Selections};
+use substrate_archive::postgres::PostgresArchive;
+use substrate_archive::selection::{EventDataSelection, EventSelection};
+use substrate_gateway::DatabaseType;
+
+fn model_lookups(result: &str) -> u64 {
+    prometheus::gather()
+        .iter()
+        .filter(|family| family.get_name() == "scan_range_model_lookups_total")
+        .flat_map(|family| family.get_metric())
+        .filter(|metric| {
+            metric
+                .get_label()
+                .iter()
+                .any(|label| label.get_name() == "result" && label.get_value() == result)
+        })
+        .map(|metric| metric.get_counter().get_value() as u64)
+        .sum()
+}
+
+#[actix_web::test]
+async fn test_scan_range_learned_across_requests() {
+    let database_url = env::var("TEST_DATABASE_URL").unwrap();
+    let pool = PgPoolOptions::new().connect(&database_url).await.unwrap();
+    let archive = PostgresArchive::new(pool, DatabaseType::Postgres, 10, 100_000, 5000);
+    let selections = Selections {
+        event: vec![EventSelection {
+            name: "Balances.Transfer".to_string(),
+            data: EventDataSelection::new(false),
+        }],
+        ..Default::default()
+    };
+    let mut options = BatchOptions {
+        limit: None,
+        from_block: 0,
+        to_block: None,
+        from_pos: None,
+        max_bytes: None,
+        time_limit_ms: None,
+        max_blocks: None,
+        include_all_blocks: false,
+        parent_hash: None,
+        progress: None,
+        selections,
+    };
+
+    let misses = model_lookups("miss");
+    let response = archive.batch(&options).await.unwrap();
+    assert!(model_lookups("miss") > misses);
+
+    let hits = model_lookups("hit");
+    options.from_block = response.next_block.unwrap();
+    archive.batch(&options).await.unwrap();
+    assert!(model_lookups("hit") > hits);
+}
+
+#[actix_web::test]
+async fn test_scan_stopped_by_time_limit_is_learned() {
+    let database_url = env::var("TEST_DATABASE_URL").unwrap();
+    let pool = PgPoolOptions::new().connect(&database_url).await.unwrap();
+    let archive = PostgresArchive::new(pool, DatabaseType::Postgres, 10, 100_000, 5000);
+    let mut options = BatchOptions {
+        limit: None,
+        from_block: 0,
+        to_block: Some(1000),
+        from_pos: None,
+        max_bytes: None,
+        time_limit_ms: Some(0),
+        max_blocks: None,
+        include_all_blocks: false,
+        parent_hash: None,
+        progress: None,
+        selections: Selections {
+            event: vec![EventSelection {
+                name: "System.ExtrinsicSuccess".to_string(),
+                data: EventDataSelection::new(false),
+            }],
+            ..Default::default()
+        },
+    };
+
+    let response = archive.batch(&options).await.unwrap();
+    // a single iteration fits into the time limit
+    assert_eq!(response.next_block, Some(10));
+
+    let hits = model_lookups("hit");
+    options.from_block = response.next_block.unwrap();
+    archive.batch(&options).await.unwrap();
+    assert!(model_lookups("hit") > hits);
+}
+
+#[actix_web::test]
+async fn test_max_blocks_limits_scan() {
+    let database_url = env::var("TEST_DATABASE_URL").unwrap();
+    let pool = PgPoolOptions::new().connect(&database_url).await.unwrap();
+    let archive = PostgresArchive::new(pool, DatabaseType::Postgres, 100, 100_000, 5000);
+    let options = BatchOptions {
+        limit: None,
+        from_block: 0,
+        to_block: None,
+        from_pos: None,
+        max_bytes: None,
+        time_limit_ms: Some(60_000),
+        max_blocks: Some(10),
+        include_all_blocks: true,
+        parent_hash: None,
+        progress: None,
+        selections: Selections::default(),
+    };
+    let response = archive.batch(&options).await.unwrap();
+    assert!(response.next_block == Some(10));
+    assert!(response.data.iter().all(|batch| batch.header.height < 10));
+}
+
+async fn scan_end(max_scan_time_limit: u16, time_limit_ms: u32) -> i32 {
+    let database_url = env::var("TEST_DATABASE_URL").unwrap();
+    let pool = PgPoolOptions::new().connect(&database_url).await.unwrap();
+    // every scan iteration covers exactly 10 blocks
+    let archive = PostgresArchive::new(pool, DatabaseType::Postgres, 10, 10, 5000)
+        .max_scan_time_limit(max_scan_time_limit);
+    let options = BatchOptions {
+        limit: None,
+        from_block: 0,
+        to_block: Some(100),
+        from_pos: None,
+        max_bytes: None,
+        time_limit_ms: Some(time_limit_ms),
+        max_blocks: None,
+        include_all_blocks: true,
+        parent_hash: None,
+        progress: None,
+        selections: Selections::default(),
+    };
+    let response = archive.batch(&options).await.unwrap();
+    response.next_block.unwrap()
+}
+
+#[actix_web::test]
+async fn test_scan_time_limit_is_clamped() {
+    // the whole range fits into the requested time limit
+    assert_eq!(scan_end(60_000, 60_000).await, 101);
+    // a request above the maximum stops at the configured limit
+    assert_eq!(scan_end(0, 60_000).await, 10);
+    // a request below the maximum is respected
+    assert_eq!(scan_end(60_000, 0).await, 10);
+}
@@ -0,0 +1,103 @@
+use serde_json::Value;
+use substrate_archive::archive::{ArchiveService, BatchOptions, Selections};
+use substrate_archive::json_schema;
+use substrate_archive::selection::{
+    CallDataSelection, CallSelection, EventDataSelection, EventSelection, EvmLogDataSelection,
+    EvmLogSelection,
+};
+use substrate_gateway::{MemoryArchive, SubstrateGateway};
+
+fn type_matches(value: &Value, schema: &Value) -> bool {
+    let types = match schema.get("type") {
+        Some(Value::String(ty)) => vec![ty.as_str()],
+        Some(Value::Array(types)) => types.iter().map(|ty| ty.as_str().unwrap()).collect(),
+        _ => return true,
+    };
+    types.iter().any(|ty| match *ty {
+        "string" => value.is_string(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => false,
+    })
+}
+
+fn assert_conforms(items: &[Value], schema: &Value) {
+    let properties = schema["properties"].as_object().unwrap();
+    for item in items {
+        let item = item.as_object().unwrap();
+        for required in schema["required"].as_array().unwrap() {
+            assert!(item.contains_key(required.as_str().unwrap()));
+        }
+        for (key, value) in item {
+            let property = properties
+                .get(key)
+                .unwrap_or_else(|| panic!("{} isn't described", key));
+            assert!(type_matches(value, property), "{} has unexpected type", key);
+        }
+    }
+}
+
+#[actix_web::test]
+async fn test_sdl_respects_support_flags() {
+    let sdl = SubstrateGateway::in_memory(MemoryArchive::default())
+        .sdl()
+        .await;
+    assert!(sdl.contains("input EventSelection {"));
+    assert!(!sdl.contains("evmLogs:"));
+    assert!(!sdl.contains("input EvmLogSelection {"));
+    assert!(!sdl.contains("contractsEvents:"));
+
+    let sdl = SubstrateGateway::in_memory(MemoryArchive::default())
+        .evm_support(true)
+        .sdl()
+        .await;
+    assert!(sdl.contains("evmLogs: [EvmLogSelection!]"));
+    assert!(sdl.contains("input EvmLogSelection {"));
+    assert!(!sdl.contains("acalaEvmExecuted:"));
+}
+
+#[actix_web::test]
+async fn test_batch_json_schema_describes_serialized_items() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/archive.json");
+    let archive = MemoryArchive::from_file(path).unwrap();
+    let options = BatchOptions {
+        limit: None,
+        from_block: 0,
+        to_block: None,
+        from_pos: None,
+        max_bytes: None,
+        time_limit_ms: None,
+        max_blocks: None,
+        include_all_blocks: false,
+        parent_hash: None,
+        progress: None,
+        selections: Selections {
+            call: vec![CallSelection {
+                name: "*".to_string(),
+                data: CallDataSelection::new(true),
+            }],
+            event: vec![EventSelection {
+                name: "*".to_string(),
+                data: EventDataSelection::new(true),
+            }],
+            evm_log: vec![EvmLogSelection {
+                contract: "*".to_string(),
+                filter: vec![],
+                data: EvmLogDataSelection::new(true),
+            }],
+            ..Default::default()
+        },
+    };
+    let response = archive.batch(&options).await.unwrap();
+    assert!(!response.data.is_empty());
+
+    let schema = json_schema::batch();
+    let defs = &schema["$defs"];
+    for batch in &response.data {
+        assert_conforms(&batch.extrinsics, &defs["Extrinsic"]);
+        assert_conforms(&batch.calls, &defs["Call"]);
+        assert_conforms(&batch.events, &defs["Event"]);
+    }
+}
@@ -0,0 +1,105 @@
+use common::launch_gateway;
+use serde_json::{json, Value};
+use substrate_archive::archive::ArchiveService;
+use substrate_archive::memory::Fixtures;
+use substrate_gateway::MemoryArchive;
+
+#[allow(dead_code)]
+mod common;
+
+fn block(height: i64, timestamp: &str) -> Value {
+    json!({
+        "id": format!("{:010}-00000", height),
+        "height": height,
+        "hash": format!("0x{:064x}", height),
+        "parent_hash": format!("0x{:064x}", height - 1),
+        "state_root": "0x",
+        "extrinsics_root": "0x",
+        "timestamp": timestamp,
+        "spec_id": "polkadot@9000",
+        "validator": null
+    })
+}
+
+fn metadata(height: i64, spec_version: i64) -> Value {
+    json!({
+        "id": format!("polkadot@{}", spec_version),
+        "spec_name": "polkadot",
+        "spec_version": spec_version,
+        "block_height": height,
+        "block_hash": format!("0x{:064x}", height),
+        "hex": "0x"
+    })
+}
+
+#[actix_web::test]
+async fn test_memory_status() {
+    let fixtures: Fixtures = serde_json::from_value(json!({
+        "blocks": [block(12, "2022-01-01T00:00:12Z"), block(10, "2022-01-01T00:00:00Z")],
+        "metadata": [metadata(0, 9000), metadata(11, 9010)]
+    }))
+    .unwrap();
+    let status = MemoryArchive::new(fixtures).status().await.unwrap();
+    assert_eq!(status.head, 12);
+    assert_eq!(status.head_hash.unwrap(), format!("0x{:064x}", 12));
+    assert_eq!(
+        status.head_timestamp.unwrap().to_rfc3339(),
+        "2022-01-01T00:00:12+00:00"
+    );
+    assert_eq!(status.lowest_height, 10);
+    assert!(status.ingest_lag_ms.unwrap() > 0);
+    assert_eq!(status.spec_version, Some(9010));
+    let tables: Vec<_> = status
+        .tables
+        .iter()
+        .map(|table| (table.name.as_str(), table.rows))
+        .collect();
+    assert_eq!(
+        tables,
+        vec![
+            ("call", Some(0)),
+            ("event", Some(0)),
+            ("extrinsic", Some(0))
+        ]
+    );
+}
+
+#[actix_web::test]
+async fn test_empty_status() {
+    let status = MemoryArchive::default().status().await.unwrap();
+    assert_eq!((status.head, status.lowest_height), (-1, -1));
+    assert!(status.head_hash.is_none());
+    assert!(status.head_timestamp.is_none());
+    assert!(status.ingest_lag_ms.is_none());
+    assert!(status.spec_version.is_none());
+}
+
+#[actix_web::test]
+async fn test_postgres_status() {
+    launch_gateway();
+    let query = "{ status { head headHash headTimestamp lowestHeight ingestLagMs specVersion tables { name rows } } }";
+    let response: Value = reqwest::Client::new()
+        .post("http://0.0.0.0:8000/graphql")
+        .json(&json!({ "query": query }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let status = &response["data"]["status"];
+    assert_eq!(status["head"], 1818666);
+    assert_eq!(status["lowestHeight"], 6);
+    assert!(status["headHash"].as_str().unwrap().starts_with("0x"));
+    assert!(status["headTimestamp"].is_string());
+    assert!(status["ingestLagMs"].as_i64().unwrap() >= 0);
+    let tables: Vec<&str> = status["tables"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|table| table["name"].as_str().unwrap())
+        .collect();
+    assert!(tables.contains(&"event"));
+    assert!(tables.contains(&"frontier_evm_log"));
+    assert!(!tables.contains(&"block"));
+}
@@ -150,6 +150,29 @@ async fn test_ethereum_transactions_wildcard_search() {
     assert!(executed.id == "0000569006-000085-5e412");
 }
 
+#[actix_web::test]
+async fn test_ethereum_transactions_with_limit() {
+    launch_gateway();
+    let query = r#"{
+        batch(limit: 1, ethereumTransactions: [{contract: "0xb654611f84a8dc429ba3cb4fda9fad236c505a1a"}]) {
+            calls
+            events
+        }
+    }"#;
+    let response: serde_json::Value = reqwest::Client::new()
+        .post("http://0.0.0.0:8000/graphql")
+        .json(&json!({ "query": query }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let batch = &response["data"]["batch"][0];
+    assert_eq!(batch["calls"][0]["id"], "0000569006-000018-5e412");
+    assert_eq!(batch["events"][0]["id"], "0000569006-000085-5e412");
+}
+
 #[actix_web::test]
 async fn test_contracts_events() {
     launch_gateway();
@@ -260,3 +283,75 @@ async fn test_acala_evm_executed_wildcard_search() {
     assert!(event.id == "0001818666-000011-af202");
     assert!(event.name == "EVM.Executed");
 }
+
+#[actix_web::test]
+async fn test_acala_evm_executed_any_contract() {
+    launch_gateway();
+    let client = Client::new();
+    let batch = client
+        .batch(json!({
+            "acalaEvmExecuted": [{"contract": "*"}],
+        }))
+        .await;
+    let event = &batch.events[0];
+    assert!(event.id == "0001818666-000011-af202");
+}
+
+#[actix_web::test]
+async fn test_acala_evm_executed_empty_topics_match_any() {
+    launch_gateway();
+    let client = Client::new();
+    let batch = client
+        .batch(json!({
+            "acalaEvmExecuted": [{
+                "contract": "0x0000000000000000000100000000000000000080",
+                "logs": [{
+                    "filter": [[], ["0x000000000000000000000000ffffd2ff9b840f6bd74f80df8e532b4d7886ffff"]],
+                }]
+            }],
+        }))
+        .await;
+    let event = &batch.events[0];
+    assert!(event.id == "0001818666-000011-af202");
+}
+
+#[actix_web::test]
+async fn test_acala_evm_executed_failed_logs() {
+    launch_gateway();
+    let client = Client::new();
+    let batch = client
+        .batch(json!({
+            "acalaEvmExecutedFailed": [{
+                "contract": "0x0000000000000000000100000000000000000081",
+                "logs": [{
+                    "filter": [["0x8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925"]],
+                }]
+            }],
+        }))
+        .await;
+    let event = &batch.events[0];
+    assert!(event.id == "0001818666-000012-af202");
+    assert!(event.name == "EVM.ExecutedFailed");
+}
+
+#[actix_web::test]
+async fn test_names_are_not_interpolated() {
+    launch_gateway();
+    let client = Client::new();
+    let batch = client
+        .batch(json!({
+            "events": [
+                {"name": "Balances.Transfer') OR ('1'='1"},
+                {"name": "Contracts.ContractEmitted"},
+            ],
+            "calls": [
+                {"name": "Balances.transfer'; DROP TABLE call; --"},
+                {"name": "Contracts.call"},
+            ],
+        }))
+        .await;
+    assert!(batch.events.len() == 1);
+    assert!(batch.events[0].name == "Contracts.ContractEmitted");
+    assert!(batch.calls.len() == 1);
+    assert!(batch.calls[0].name == "Contracts.call");
+}
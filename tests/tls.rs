@@ -0,0 +1,34 @@
+use actix_web::rt::time::sleep;
+use common::{is_ready_over_tls, launch_tls_gateway, tls_client, tls_fixture};
+use std::path::Path;
+use std::time::Duration;
+use std::{env, fs};
+
+#[allow(dead_code)]
+mod common;
+
+fn install_certificate(dir: &Path, cert: &str, key: &str) {
+    fs::copy(tls_fixture(cert), dir.join("cert.pem")).unwrap();
+    fs::copy(tls_fixture(key), dir.join("key.pem")).unwrap();
+}
+
+#[actix_web::test]
+async fn test_tls_with_required_client_certificates_and_reload() {
+    let dir = env::temp_dir().join(format!("tls-{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir).unwrap();
+    install_certificate(&dir, "server.pem", "server-key.pem");
+    launch_tls_gateway(dir.join("cert.pem"), dir.join("key.pem"), true);
+
+    assert!(is_ready_over_tls(&tls_client("ca.pem", true)).await);
+    assert!(!is_ready_over_tls(&tls_client("ca.pem", false)).await);
+    assert!(!is_ready_over_tls(&tls_client("ca2.pem", true)).await);
+
+    // the certificate issued by another CA is served after SIGHUP
+    install_certificate(&dir, "server2.pem", "server2-key.pem");
+    unsafe { libc::kill(libc::getpid(), libc::SIGHUP) };
+    sleep(Duration::from_millis(500)).await;
+    assert!(is_ready_over_tls(&tls_client("ca2.pem", true)).await);
+    assert!(!is_ready_over_tls(&tls_client("ca.pem", true)).await);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
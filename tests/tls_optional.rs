@@ -0,0 +1,17 @@
+use common::{is_ready_over_tls, launch_tls_gateway, tls_client, tls_fixture};
+
+#[allow(dead_code)]
+mod common;
+
+#[actix_web::test]
+async fn test_tls_with_optional_client_certificates() {
+    launch_tls_gateway(
+        tls_fixture("server.pem"),
+        tls_fixture("server-key.pem"),
+        false,
+    );
+
+    assert!(is_ready_over_tls(&tls_client("ca.pem", true)).await);
+    // a client without a certificate is let in as well
+    assert!(is_ready_over_tls(&tls_client("ca.pem", false)).await);
+}
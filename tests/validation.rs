@@ -0,0 +1,130 @@
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{Executor, Pool, Postgres};
+use std::env;
+use substrate_archive::postgres::hints::{IndexHints, QueryShape};
+use substrate_archive::postgres::validation::{Feature, SchemaInspector};
+use substrate_gateway::{DatabaseType, SubstrateGateway};
+
+const FEATURES: [Feature; 5] = [
+    Feature::Substrate,
+    Feature::Evm,
+    Feature::Contracts,
+    Feature::Gear,
+    Feature::Acala,
+];
+
+async fn connect() -> Pool<Postgres> {
+    let database_url = env::var("TEST_DATABASE_URL").unwrap();
+    PgPoolOptions::new().connect(&database_url).await.unwrap()
+}
+
+async fn connect_to_empty_schema() -> Pool<Postgres> {
+    connect()
+        .await
+        .execute("CREATE SCHEMA IF NOT EXISTS gateway_empty")
+        .await
+        .unwrap();
+    let database_url = env::var("TEST_DATABASE_URL").unwrap();
+    PgPoolOptions::new()
+        .max_connections(1)
+        .after_connect(|connection, _meta| {
+            Box::pin(async move {
+                connection
+                    .execute("SET search_path = gateway_empty")
+                    .await?;
+                Ok(())
+            })
+        })
+        .connect(&database_url)
+        .await
+        .unwrap()
+}
+
+#[actix_web::test]
+async fn test_schema_has_everything_for_postgres() {
+    let inspector = SchemaInspector::load(&connect().await, DatabaseType::Postgres)
+        .await
+        .unwrap();
+    for feature in FEATURES {
+        assert!(inspector.missing(feature).is_empty());
+    }
+}
+
+#[actix_web::test]
+async fn test_cockroach_indexes_are_required() {
+    let inspector = SchemaInspector::load(&connect().await, DatabaseType::Cockroach)
+        .await
+        .unwrap();
+    assert!(inspector.missing(Feature::Substrate) == vec!["index idx_event__name__block"]);
+    assert!(inspector.missing(Feature::Gear).is_empty());
+}
+
+#[actix_web::test]
+async fn test_missing_tables_reported() {
+    let inspector = SchemaInspector::load(&connect_to_empty_schema().await, DatabaseType::Postgres)
+        .await
+        .unwrap();
+    assert!(
+        inspector.missing(Feature::Evm)
+            == vec![
+                "table frontier_evm_log",
+                "table frontier_ethereum_transaction"
+            ]
+    );
+}
+
+#[actix_web::test]
+async fn test_gateway_refuses_to_start() {
+    let pool = connect_to_empty_schema().await;
+    let result = SubstrateGateway::new(pool, DatabaseType::Postgres)
+        .run()
+        .await;
+    let err = result.unwrap_err();
+    assert!(err.to_string().contains("table block"));
+}
+
+#[actix_web::test]
+async fn test_database_type_detected() {
+    let (database_type, version) = DatabaseType::detect(&connect().await).await.unwrap();
+    assert!(database_type == DatabaseType::Postgres);
+    assert!(version.starts_with("PostgreSQL"));
+}
+
+#[test]
+fn test_index_hints_overridden() {
+    let json = r#"{
+        "cockroach": {
+            "evm_log_by_contract_topic": null,
+            "eth_transaction_by_contract_sighash": "idx_eth_transaction__contract"
+        }
+    }"#;
+    let hints = IndexHints::from_json(json).unwrap();
+    let table = hints.table(QueryShape::EventByName, &DatabaseType::Postgres);
+    assert!(table == "event");
+    let table = hints.table(
+        QueryShape::EthTransactionByContractSighash,
+        &DatabaseType::Cockroach,
+    );
+    assert!(table == "frontier_ethereum_transaction@idx_eth_transaction__contract");
+    let table = hints.table(QueryShape::EvmLogByContractTopic, &DatabaseType::Cockroach);
+    assert!(table == "frontier_evm_log");
+    let table = hints.table(QueryShape::EventByName, &DatabaseType::Cockroach);
+    assert!(table == "event@idx_event__name__block");
+}
+
+#[test]
+fn test_postgres_index_hints_rejected() {
+    let json = r#"{"postgres": {"event_by_name": "idx_event__name"}}"#;
+    assert!(IndexHints::from_json(json).is_err());
+}
+
+#[actix_web::test]
+async fn test_hinted_indexes_are_required() {
+    let hints =
+        IndexHints::from_json(r#"{"cockroach": {"event_by_name": "idx_unknown"}}"#).unwrap();
+    let inspector = SchemaInspector::load(&connect().await, DatabaseType::Cockroach)
+        .await
+        .unwrap()
+        .index_hints(hints);
+    assert!(inspector.missing(Feature::Substrate) == vec!["index idx_unknown"]);
+}